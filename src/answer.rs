@@ -0,0 +1,85 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+// What a solver hands back. Integers that fit in an i64 are always stored as `Int`,
+// so `Answer::from(5u64) == Answer::Int(5)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+  Int(i64),
+  BigInt(u128),
+  // Free-form text, which may span several lines
+  Text(String),
+}
+
+impl fmt::Display for Answer {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Answer::Int(val) => write!(f, "{}", val),
+      Answer::BigInt(val) => write!(f, "{}", val),
+      Answer::Text(val) => write!(f, "{}", val),
+    }
+  }
+}
+
+impl From<i64> for Answer {
+  fn from(val: i64) -> Answer {
+    Answer::Int(val)
+  }
+}
+
+impl From<u128> for Answer {
+  fn from(val: u128) -> Answer {
+    match i64::try_from(val) {
+      Ok(val) => Answer::Int(val),
+      Err(_) => Answer::BigInt(val),
+    }
+  }
+}
+
+impl From<u64> for Answer {
+  fn from(val: u64) -> Answer {
+    Answer::from(val as u128)
+  }
+}
+
+impl From<usize> for Answer {
+  fn from(val: usize) -> Answer {
+    Answer::from(val as u128)
+  }
+}
+
+impl From<String> for Answer {
+  fn from(val: String) -> Answer {
+    Answer::Text(val)
+  }
+}
+
+impl From<&str> for Answer {
+  fn from(val: &str) -> Answer {
+    Answer::Text(String::from(val))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_from() {
+    assert_eq!(Answer::Int(5), Answer::from(5u64));
+    assert_eq!(Answer::Int(5), Answer::from(5usize));
+    assert_eq!(Answer::Int(-5), Answer::from(-5i64));
+    assert_eq!(Answer::BigInt(u64::MAX as u128), Answer::from(u64::MAX));
+    assert_eq!(Answer::Text(String::from("abc")), Answer::from("abc"));
+  }
+
+  #[test]
+  fn test_display() {
+    assert_eq!("-12", Answer::Int(-12).to_string());
+    assert_eq!(
+      "340282366920938463463374607431768211455",
+      Answer::BigInt(u128::MAX).to_string()
+    );
+    assert_eq!("#..\n.#.", Answer::from("#..\n.#.").to_string());
+  }
+}
@@ -0,0 +1,140 @@
+// Known-good answers for our own puzzle inputs, read from `answers.toml`:
+//
+//   [day1]
+//   part1 = 982464
+//   part2 = 162292410
+//
+// `verify` compares every registered solver against these to catch refactors that
+// quietly change a result.
+
+use crate::config::ROOT;
+use crate::{kv, Answer, AocError};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, PartialEq)]
+pub struct KnownAnswers {
+  answers: BTreeMap<(u32, u32), String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+  Pass,
+  // The solver failed, or gave a different answer
+  Fail,
+  // There's no known answer, or no solver for a known answer
+  Missing,
+}
+
+impl KnownAnswers {
+  pub fn default_path() -> PathBuf {
+    Path::new(ROOT).join("answers.toml")
+  }
+
+  pub fn load(path: &Path) -> Result<KnownAnswers, AocError> {
+    let text =
+      fs::read_to_string(path).map_err(|e| AocError::Io(format!("{}: {}", path.display(), e)))?;
+    KnownAnswers::parse(&text).map_err(|err| err.in_file(path))
+  }
+
+  pub fn parse(text: &str) -> Result<KnownAnswers, AocError> {
+    let mut answers = BTreeMap::new();
+    for entry in kv::parse(text)? {
+      let day = entry
+        .section
+        .strip_prefix("day")
+        .and_then(|d| d.parse().ok());
+      let part = entry.key.strip_prefix("part").and_then(|p| p.parse().ok());
+      let (day, part) = match (day, part) {
+        (Some(day), Some(part)) => (day, part),
+        _ => {
+          return Err(AocError::InvalidArgument(format!(
+            "expected a partN key in a [dayN] section on line {}",
+            entry.line
+          )))
+        }
+      };
+      if answers.insert((day, part), entry.value).is_some() {
+        return Err(AocError::InvalidArgument(format!(
+          "duplicate answer for day {} part {} on line {}",
+          day, part, entry.line
+        )));
+      }
+    }
+    Ok(KnownAnswers { answers })
+  }
+
+  pub fn get(&self, day: u32, part: u32) -> Option<&str> {
+    self.answers.get(&(day, part)).map(String::as_str)
+  }
+
+  // Every (day, part) pair with a known answer, in order
+  pub fn pairs(&self) -> Vec<(u32, u32)> {
+    self.answers.keys().copied().collect()
+  }
+}
+
+pub fn check(expected: Option<&str>, actual: &Result<Answer, AocError>) -> Verdict {
+  match (expected, actual) {
+    (None, _) => Verdict::Missing,
+    (Some(expected), Ok(answer)) if answer.to_string() == expected => Verdict::Pass,
+    _ => Verdict::Fail,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse() {
+    let answers = KnownAnswers::parse("[day1]\npart1 = 5\n\n[day14]\npart1 = \"a\\nb\"\n").unwrap();
+    assert_eq!(Some("5"), answers.get(1, 1));
+    assert_eq!(Some("a\nb"), answers.get(14, 1));
+    assert_eq!(None, answers.get(1, 2));
+    assert_eq!(vec![(1, 1), (14, 1)], answers.pairs());
+
+    for text in &[
+      "part1 = 1",
+      "[day1]\nanswer = 1",
+      "[dayx]\npart1 = 1",
+      "[day1]\npart1 = 1\npart1 = 2",
+    ] {
+      match KnownAnswers::parse(text) {
+        Err(AocError::InvalidArgument(_)) => {}
+        other => panic!("expected {:?} to be rejected, got {:?}", text, other),
+      }
+    }
+  }
+
+  #[test]
+  fn test_check() {
+    assert_eq!(Verdict::Pass, check(Some("42"), &Ok(Answer::Int(42))));
+    assert_eq!(Verdict::Fail, check(Some("42"), &Ok(Answer::Int(41))));
+    assert_eq!(
+      Verdict::Fail,
+      check(Some("42"), &Err(AocError::DayNotFound(3)))
+    );
+    assert_eq!(Verdict::Missing, check(None, &Ok(Answer::Int(42))));
+  }
+
+  // Once a day has a recorded answer, every part it solves has one. Days with none yet,
+  // like a freshly scaffolded one, are skipped
+  #[test]
+  fn test_every_solution_has_an_answer() {
+    let answers = KnownAnswers::load(&KnownAnswers::default_path()).unwrap();
+    let recorded: Vec<u32> = answers.pairs().into_iter().map(|(day, _)| day).collect();
+    for (day, part) in crate::solved() {
+      if !recorded.contains(&day) {
+        continue;
+      }
+      assert!(
+        answers.get(day, part).is_some(),
+        "day {} part {}",
+        day,
+        part
+      );
+    }
+  }
+}
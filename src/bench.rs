@@ -0,0 +1,246 @@
+// Repeated timing of solvers for `bench`, and the baseline file (bench.toml) that later
+// runs are compared against:
+//
+//   [day11]
+//   parse = 440000 # median nanoseconds
+//   part1 = 182000000
+
+use crate::config::ROOT;
+use crate::{kv, AocError};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, PartialEq)]
+pub struct Options {
+  pub warmup: u32,
+  pub runs: u32,
+  // How much slower than the baseline, in percent, counts as a regression
+  pub threshold: f64,
+  // Overwrite the baseline with the results of this run
+  pub save: bool,
+}
+
+impl Default for Options {
+  fn default() -> Options {
+    Options {
+      warmup: 3,
+      runs: 20,
+      threshold: 10.0,
+      save: false,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stats {
+  pub min: Duration,
+  pub median: Duration,
+  pub mean: Duration,
+  pub stddev: Duration,
+}
+
+impl Stats {
+  pub fn new(samples: &[Duration]) -> Stats {
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable();
+    let n = sorted.len();
+    if n == 0 {
+      return Stats {
+        min: Duration::default(),
+        median: Duration::default(),
+        mean: Duration::default(),
+        stddev: Duration::default(),
+      };
+    }
+    let median = if n % 2 == 1 {
+      sorted[n / 2]
+    } else {
+      (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+    };
+    let secs: Vec<f64> = sorted.iter().map(Duration::as_secs_f64).collect();
+    let mean = secs.iter().sum::<f64>() / n as f64;
+    let variance = secs.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n as f64;
+    Stats {
+      min: sorted[0],
+      median,
+      mean: Duration::from_secs_f64(mean),
+      stddev: Duration::from_secs_f64(variance.sqrt()),
+    }
+  }
+
+  // Bytes of input per second, based on the median run
+  pub fn throughput(&self, bytes: usize) -> f64 {
+    bytes as f64 / self.median.as_secs_f64().max(f64::MIN_POSITIVE)
+  }
+}
+
+// Calls `f` `warmup` times untimed, then `runs` times timed, stopping at the first error
+pub fn sample<T, E, F>(options: &Options, mut f: F) -> Result<Vec<Duration>, E>
+where
+  F: FnMut() -> Result<T, E>,
+{
+  for _ in 0..options.warmup {
+    f()?;
+  }
+  let mut samples = Vec::with_capacity(options.runs as usize);
+  for _ in 0..options.runs {
+    let start = Instant::now();
+    f()?;
+    samples.push(start.elapsed());
+  }
+  Ok(samples)
+}
+
+// The change from `baseline` to `current`, in percent
+pub fn change(baseline: Duration, current: Duration) -> f64 {
+  (current.as_secs_f64() / baseline.as_secs_f64().max(f64::MIN_POSITIVE) - 1.0) * 100.0
+}
+
+// Stands in for the part when benchmarking a day's parse, so it sorts first
+pub const PARSE: u32 = 0;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Baseline {
+  medians: BTreeMap<(u32, u32), Duration>,
+}
+
+impl Baseline {
+  pub fn default_path() -> PathBuf {
+    Path::new(ROOT).join("bench.toml")
+  }
+
+  // A missing file is an empty baseline
+  pub fn load(path: &Path) -> Result<Baseline, AocError> {
+    if !path.exists() {
+      return Ok(Baseline::default());
+    }
+    let text =
+      fs::read_to_string(path).map_err(|e| AocError::Io(format!("{}: {}", path.display(), e)))?;
+    Baseline::parse(&text).map_err(|err| err.in_file(path))
+  }
+
+  pub fn parse(text: &str) -> Result<Baseline, AocError> {
+    let mut medians = BTreeMap::new();
+    for entry in kv::parse(text)? {
+      let day = entry
+        .section
+        .strip_prefix("day")
+        .and_then(|d| d.parse().ok());
+      let part = match entry.key.as_str() {
+        "parse" => Some(PARSE),
+        key => key
+          .strip_prefix("part")
+          .and_then(|p| p.parse().ok())
+          .filter(|&p| p != PARSE),
+      };
+      let nanos = entry.value.parse().ok();
+      match (day, part, nanos) {
+        (Some(day), Some(part), Some(nanos)) => {
+          medians.insert((day, part), Duration::from_nanos(nanos));
+        }
+        _ => {
+          return Err(AocError::InvalidArgument(format!(
+            "expected parse or partN = <nanoseconds> in a [dayN] section on line {}",
+            entry.line
+          )))
+        }
+      }
+    }
+    Ok(Baseline { medians })
+  }
+
+  pub fn save(&self, path: &Path) -> Result<(), AocError> {
+    fs::write(path, self.to_string())
+      .map_err(|e| AocError::Io(format!("{}: {}", path.display(), e)))
+  }
+
+  pub fn get(&self, day: u32, part: u32) -> Option<Duration> {
+    self.medians.get(&(day, part)).copied()
+  }
+
+  pub fn insert(&mut self, day: u32, part: u32, median: Duration) {
+    self.medians.insert((day, part), median);
+  }
+}
+
+impl fmt::Display for Baseline {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    writeln!(
+      f,
+      "# Median parse and solve times in nanoseconds, written by `bench --save`"
+    )?;
+    let mut day = None;
+    for (&(d, part), median) in &self.medians {
+      if day != Some(d) {
+        write!(f, "\n[day{}]\n", d)?;
+        day = Some(d);
+      }
+      match part {
+        PARSE => writeln!(f, "parse = {}", median.as_nanos())?,
+        _ => writeln!(f, "part{} = {}", part, median.as_nanos())?,
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn ms(millis: &[u64]) -> Vec<Duration> {
+    millis.iter().map(|&m| Duration::from_millis(m)).collect()
+  }
+
+  #[test]
+  fn test_stats() {
+    let stats = Stats::new(&ms(&[4, 2, 6, 8]));
+    assert_eq!(Duration::from_millis(2), stats.min);
+    assert_eq!(Duration::from_millis(5), stats.median);
+    assert_eq!(Duration::from_millis(5), stats.mean);
+    assert_eq!(2236, stats.stddev.as_micros());
+    assert_eq!(Duration::from_millis(6), Stats::new(&ms(&[9, 6, 1])).median);
+    assert_eq!(2000.0, Stats::new(&ms(&[500])).throughput(1000).round());
+  }
+
+  #[test]
+  fn test_sample() {
+    let mut calls = 0;
+    let options = Options {
+      warmup: 2,
+      runs: 5,
+      ..Options::default()
+    };
+    let samples = sample(&options, || {
+      calls += 1;
+      Ok::<_, ()>(())
+    })
+    .unwrap();
+    assert_eq!((7, 5), (calls, samples.len()));
+    assert_eq!(Err("no"), sample(&options, || Err::<(), _>("no")));
+  }
+
+  #[test]
+  fn test_change() {
+    let change = change(Duration::from_millis(100), Duration::from_millis(125));
+    assert!((change - 25.0).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_baseline_round_trip() {
+    let mut baseline = Baseline::default();
+    baseline.insert(11, 1, Duration::from_nanos(182_000_000));
+    baseline.insert(1, 2, Duration::from_nanos(41_000));
+    baseline.insert(1, 1, Duration::from_nanos(51_000));
+    baseline.insert(1, PARSE, Duration::from_nanos(9_000));
+    let text = baseline.to_string();
+    assert!(text.contains("[day1]\nparse = 9000\npart1 = 51000\npart2 = 41000\n\n[day11]\n"));
+    assert_eq!(baseline, Baseline::parse(&text).unwrap());
+
+    assert!(Baseline::parse("[day1]\npart1 = \"fast\"").is_err());
+    assert!(Baseline::parse("[day1]\npart0 = 5").is_err());
+  }
+}
@@ -0,0 +1,255 @@
+// Answers that have already been computed, kept in cache.toml so that running a day
+// again on the same input with the same solver doesn't solve it again. Each answer is
+// keyed by the hashes of the input and of the solver's source, and stored with its
+// kind and the nanoseconds it took to parse ("-" if the part shared an earlier part's
+// model) and to solve:
+//
+//   [day11.part1]
+//   "57087cca9eb4fcaa 8d3e0d3f06a0b1c2" = "int 7801234 2510000000 2093"
+//
+// Entries that don't read as one, e.g. from before timings were stored, are dropped.
+
+use crate::config::ROOT;
+use crate::{kv, Answer, AocError};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// A computed answer, and how long it took the first time
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+  pub answer: Answer,
+  // None when the part reused the model parsed for an earlier part of the day
+  pub parse: Option<Duration>,
+  pub solve: Duration,
+}
+
+impl Entry {
+  fn to_text(&self) -> String {
+    let kind = match self.answer {
+      Answer::Int(_) => "int",
+      Answer::BigInt(_) => "bigint",
+      Answer::Text(_) => "text",
+    };
+    let parse = self
+      .parse
+      .map_or(String::from("-"), |parse| parse.as_nanos().to_string());
+    format!(
+      "{} {} {} {}",
+      kind,
+      parse,
+      self.solve.as_nanos(),
+      self.answer
+    )
+  }
+
+  fn parse(text: &str) -> Option<Entry> {
+    let mut fields = text.splitn(4, ' ');
+    let (kind, parse, solve, answer) = (
+      fields.next()?,
+      fields.next()?,
+      fields.next()?,
+      fields.next()?,
+    );
+    let answer = match kind {
+      "int" => Answer::Int(answer.parse().ok()?),
+      "bigint" => Answer::BigInt(answer.parse().ok()?),
+      "text" => Answer::Text(String::from(answer)),
+      _ => return None,
+    };
+    let parse = match parse {
+      "-" => None,
+      nanos => Some(Duration::from_nanos(nanos.parse().ok()?)),
+    };
+    Some(Entry {
+      answer,
+      parse,
+      solve: Duration::from_nanos(solve.parse().ok()?),
+    })
+  }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Cache {
+  // (day, part, input hash) to (solver version, entry)
+  answers: BTreeMap<(u32, u32, String), (String, Entry)>,
+}
+
+impl Cache {
+  pub fn default_path() -> PathBuf {
+    Path::new(ROOT).join("cache.toml")
+  }
+
+  // A missing file is an empty cache
+  pub fn load(path: &Path) -> Result<Cache, AocError> {
+    if !path.exists() {
+      return Ok(Cache::default());
+    }
+    let text =
+      fs::read_to_string(path).map_err(|e| AocError::Io(format!("{}: {}", path.display(), e)))?;
+    Cache::parse(&text).map_err(|err| err.in_file(path))
+  }
+
+  pub fn parse(text: &str) -> Result<Cache, AocError> {
+    let mut answers = BTreeMap::new();
+    for entry in kv::parse(text)? {
+      let mut section = entry.section.split('.');
+      let day = section
+        .next()
+        .and_then(|s| s.strip_prefix("day")?.parse().ok());
+      let part = section
+        .next()
+        .and_then(|s| s.strip_prefix("part")?.parse().ok());
+      let mut key = entry.key.split(' ');
+      match (day, part, key.next(), key.next(), key.next()) {
+        (Some(day), Some(part), Some(input), Some(version), None) => {
+          if let Some(value) = Entry::parse(&entry.value) {
+            answers.insert(
+              (day, part, String::from(input)),
+              (String::from(version), value),
+            );
+          }
+        }
+        _ => {
+          return Err(AocError::InvalidArgument(format!(
+            "expected \"<input hash> <version>\" = \"<kind> <parse> <solve> <answer>\" in a [dayN.partN] section on line {}",
+            entry.line
+          )))
+        }
+      }
+    }
+    Ok(Cache { answers })
+  }
+
+  pub fn save(&self, path: &Path) -> Result<(), AocError> {
+    fs::write(path, self.to_text()).map_err(|e| AocError::Io(format!("{}: {}", path.display(), e)))
+  }
+
+  fn to_text(&self) -> String {
+    let mut text = String::from("# Answers computed before, written by running days\n");
+    let mut section = None;
+    for ((day, part, input), (version, entry)) in &self.answers {
+      if section != Some((day, part)) {
+        text.push_str(&format!("\n[day{}.part{}]\n", day, part));
+        section = Some((day, part));
+      }
+      let key = format!("{} {}", input, version);
+      text.push_str(&format!(
+        "{} = {}\n",
+        kv::quote(&key),
+        kv::quote(&entry.to_text())
+      ));
+    }
+    text
+  }
+
+  // The answer for the input with hash `input`, if it was computed by the same
+  // version of the solver
+  pub fn get(&self, day: u32, part: u32, input: &str, version: &str) -> Option<&Entry> {
+    match self.answers.get(&(day, part, String::from(input))) {
+      Some((cached, entry)) if cached == version => Some(entry),
+      _ => None,
+    }
+  }
+
+  // Replaces any answer for the same input from another version of the solver
+  pub fn insert(&mut self, day: u32, part: u32, input: &str, version: &str, entry: Entry) {
+    self.answers.insert(
+      (day, part, String::from(input)),
+      (String::from(version), entry),
+    );
+  }
+
+  pub fn len(&self) -> usize {
+    self.answers.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.answers.is_empty()
+  }
+}
+
+// Deletes the cache file, returning how many answers it held
+pub fn clear(path: &Path) -> Result<usize, AocError> {
+  let cache = Cache::load(path)?;
+  if path.exists() {
+    fs::remove_file(path).map_err(|e| AocError::Io(format!("{}: {}", path.display(), e)))?;
+  }
+  Ok(cache.len())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::env;
+  use std::process;
+
+  fn entry(answer: impl Into<Answer>) -> Entry {
+    Entry {
+      answer: answer.into(),
+      parse: Some(Duration::from_micros(15)),
+      solve: Duration::from_millis(2),
+    }
+  }
+
+  #[test]
+  fn test_get() {
+    let mut cache = Cache::default();
+    cache.insert(11, 1, "57087cca9eb4fcaa", "v1", entry(2093i64));
+    assert_eq!(
+      Some(&entry(2093i64)),
+      cache.get(11, 1, "57087cca9eb4fcaa", "v1")
+    );
+    assert_eq!(None, cache.get(11, 1, "57087cca9eb4fcaa", "v2"));
+    assert_eq!(None, cache.get(11, 2, "57087cca9eb4fcaa", "v1"));
+    assert_eq!(None, cache.get(11, 1, "0000000000000000", "v1"));
+
+    cache.insert(11, 1, "57087cca9eb4fcaa", "v2", entry(2094i64));
+    assert_eq!(1, cache.len());
+    assert_eq!(
+      Some(&entry(2094i64)),
+      cache.get(11, 1, "57087cca9eb4fcaa", "v2")
+    );
+  }
+
+  #[test]
+  fn test_round_trip() {
+    let mut cache = Cache::default();
+    cache.insert(11, 1, "57087cca9eb4fcaa", "v1", entry(2093i64));
+    cache.insert(3, 2, "57087cca9eb4fcaa", "v1", entry("#.\n.#"));
+    cache.insert(3, 2, "af63dc4c8601ec8c", "v1", entry("7"));
+    let shared = Entry {
+      parse: None,
+      ..entry(u128::MAX)
+    };
+    cache.insert(10, 2, "af63dc4c8601ec8c", "v1", shared);
+    let text = cache.to_text();
+    assert!(
+      text.contains("\n[day3.part2]\n\"57087cca9eb4fcaa v1\" = \"text 15000 2000000 #.\\n.#\"\n")
+    );
+    assert!(text.contains("\"af63dc4c8601ec8c v1\" = \"bigint - 2000000 3402823"));
+    // Answers keep their kind, so a text "7" stays text
+    assert_eq!(cache, Cache::parse(&text).unwrap());
+
+    assert!(Cache::parse("[day3]\n\"a v1\" = \"int - 0 7\"").is_err());
+    assert!(Cache::parse("[day3.part1]\n\"a\" = \"int - 0 7\"").is_err());
+    // Entries from before kinds and timings were stored are dropped
+    assert!(Cache::parse("[day3.part1]\n\"a v1\" = \"7\"")
+      .unwrap()
+      .is_empty());
+  }
+
+  #[test]
+  fn test_clear() {
+    let path = env::temp_dir().join(format!("aoc2020-cache-{}.toml", process::id()));
+    let mut cache = Cache::default();
+    cache.insert(1, 1, "57087cca9eb4fcaa", "v1", entry(982464i64));
+    cache.save(&path).unwrap();
+    assert_eq!(cache, Cache::load(&path).unwrap());
+
+    assert_eq!(Ok(1), clear(&path));
+    assert!(!path.exists());
+    assert_eq!(Ok(0), clear(&path));
+  }
+}
@@ -0,0 +1,126 @@
+// Cooperative cancellation for solvers run with a time limit. `with_timeout` runs the
+// solver on a worker thread that carries a cancellation token, and long-running loops
+// and recursions call `check()` so that a cancelled solver stops instead of spinning
+// forever. Nothing can stop a worker that doesn't call it: once timed out, it keeps
+// running detached, using a CPU, until it finishes by itself.
+
+use crate::AocError;
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Clone, Debug, Default)]
+pub struct Token(Arc<AtomicBool>);
+
+impl Token {
+  pub fn new() -> Token {
+    Token::default()
+  }
+
+  pub fn cancel(&self) {
+    self.0.store(true, Ordering::Relaxed);
+  }
+
+  pub fn is_cancelled(&self) -> bool {
+    self.0.load(Ordering::Relaxed)
+  }
+}
+
+thread_local! {
+  // The token for the solver running on this thread, and its time limit
+  static CURRENT: RefCell<Option<(Token, Duration)>> = const { RefCell::new(None) };
+}
+
+// Fails with `Timeout` if the solver running on this thread has been cancelled. Outside
+// of `with_timeout` this always succeeds.
+pub fn check() -> Result<(), AocError> {
+  CURRENT.with(|current| match &*current.borrow() {
+    Some((token, limit)) if token.is_cancelled() => Err(AocError::Timeout(*limit)),
+    _ => Ok(()),
+  })
+}
+
+// Runs `f` on a worker thread, giving up with `Timeout` if it takes longer than `limit`.
+// The worker's token is cancelled at that point so it stops at its next `check()`, or
+// carries on in the background if it never calls it.
+pub fn with_timeout<T, F>(limit: Duration, f: F) -> Result<T, AocError>
+where
+  T: Send + 'static,
+  F: FnOnce() -> Result<T, AocError> + Send + 'static,
+{
+  let token = Token::new();
+  let (sender, receiver) = mpsc::channel();
+  let worker = {
+    let token = token.clone();
+    thread::spawn(move || {
+      CURRENT.with(|current| *current.borrow_mut() = Some((token, limit)));
+      // The receiver is gone if we've already timed out
+      let _ = sender.send(f());
+    })
+  };
+
+  match receiver.recv_timeout(limit) {
+    Ok(result) => result,
+    Err(mpsc::RecvTimeoutError::Timeout) => {
+      token.cancel();
+      Err(AocError::Timeout(limit))
+    }
+    // The worker hung up without sending, so it must have panicked
+    Err(mpsc::RecvTimeoutError::Disconnected) => match worker.join() {
+      Err(panic) => std::panic::resume_unwind(panic),
+      Ok(()) => unreachable!("the worker always sends a result before finishing"),
+    },
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::time::Instant;
+
+  #[test]
+  fn test_check_without_token() {
+    assert_eq!(Ok(()), check());
+  }
+
+  #[test]
+  fn test_finishes_in_time() {
+    assert_eq!(Ok(42), with_timeout(Duration::from_secs(10), || Ok(42)));
+    assert_eq!(
+      Err(AocError::DayNotFound(25)),
+      with_timeout(Duration::from_secs(10), || Err::<(), _>(
+        AocError::DayNotFound(25)
+      ))
+    );
+  }
+
+  #[test]
+  fn test_cancels_long_loops() {
+    let (sender, receiver) = mpsc::channel();
+    let limit = Duration::from_millis(20);
+    let start = Instant::now();
+    let result = with_timeout(limit, move || loop {
+      if let Err(err) = check() {
+        sender.send(err.clone()).unwrap();
+        return Err::<(), _>(err);
+      }
+    });
+    assert_eq!(Err(AocError::Timeout(limit)), result);
+    assert!(start.elapsed() < Duration::from_secs(5));
+    // The worker sees the cancellation and stops too
+    assert_eq!(
+      Ok(AocError::Timeout(limit)),
+      receiver.recv_timeout(Duration::from_secs(5))
+    );
+  }
+
+  #[test]
+  #[should_panic(expected = "boom")]
+  fn test_worker_panics() {
+    let _ = with_timeout(Duration::from_secs(10), || -> Result<(), AocError> {
+      panic!("boom")
+    });
+  }
+}
@@ -0,0 +1,781 @@
+use crate::{bench, difftest, generate, AocError};
+use std::time::Duration;
+
+pub const USAGE: &str = "Usage: aoc2020 [options] <command>
+
+Commands:
+  <day> <part>    Solve one part of a day and print the answer
+  <day>           Solve every part of a day and print a timing table
+  all             Solve every registered day and print a timing table
+  list            List the registered days
+  verify          Solve every registered day and compare against answers.toml
+  new <day>       Create src/dayN.rs from a template, register it in lib.rs and
+                  create an empty input file and examples/dayN/1.txt. Existing
+                  files are never touched
+  fetch <day>     Download the day's input to input/dayN.txt, unless it's there
+  submit <day> <part>
+                  Solve one part and send the answer to the puzzle site, unless
+                  submissions.toml shows how that would go
+  watch <day>     Re-run the day's example tests and both parts whenever its input
+                  or src/dayN.rs changes, showing how each answer changed
+  bench [<day> [<part>]]
+                  Time each solver over repeated runs and compare the medians
+                  against the baseline in bench.toml
+  cache clear     Forget every answer cached by running days
+  diff-test [<day>]
+                  Solve each part that has more than one implementation with all
+                  of them, on the day's examples and generated inputs, and show
+                  the first input where they disagree
+  gen <day>       Print a random input for the day with a solution, in the format
+                  its parser expects
+
+Options:
+  --format <format>   Print the results of running days as text (the default), or
+                      as json: one object per line for each part, with the
+                      answer, timings, input and any error
+  -j, --jobs <n>      Run up to <n> parts at once when running days or verifying
+                      (default 1). Each day is still parsed once, and results
+                      are still printed in order
+  -t, --timeout <time>
+                      Cancel any parse or part that runs for longer than <time>, e.g.
+                      30s or 500ms, when running days, verifying or watching.
+                      diff-test skips inputs that take longer (default 1s)
+  --no-cache          Solve every part when running days, without reading or
+                      writing the answers cached in cache.toml
+  --trace <path>      Record spans inside the solvers when running days, and write
+                      them to <path>: Chrome trace JSON if it ends in .json, and
+                      folded stacks for flamegraph tools otherwise. Needs a build
+                      with --features trace, and implies --no-cache
+  -i, --input <path>  Read the input from <path> instead of input/dayN.txt, or
+                      from stdin if <path> is \"-\". Only valid for a single day,
+                      and watch needs a file
+  --warmup <n>        Untimed runs before benchmarking (default 3)
+  --runs <n>          Timed runs per solver when benchmarking (default 20)
+  --threshold <pct>   Flag benchmarks this much slower than the baseline
+                      (default 10)
+  --save              Save the benchmark results as the new baseline
+  --seed <n>          Seed the inputs generated by gen or diff-test (default 1)
+  --cases <n>         How many inputs diff-test generates per day (default 100)
+  --size <n>          Roughly how many lines or records gen makes (default 100)
+  --invalid <pct>     The share of records gen makes invalid, for days that count
+                      the valid ones (default 25)
+  -h, --help          Print this message
+
+Input files are looked up in $AOC_INPUT_DIR if it is set, then in the input_dir
+setting of aoc.toml (or the file named by $AOC_CONFIG), then in input/. The
+answers setting of aoc.toml names a different known-answers file for verify,
+and the baseline setting a different baseline for bench. Running days caches
+each answer in cache.toml, or the file named by the cache setting. verify, bench
+and submit always solve.
+
+fetch and submit need the session cookie from the puzzle site, from $AOC_SESSION
+or the session setting of aoc.toml. $AOC_BASE_URL or base_url sets where the site
+is, and the history setting a different submission history.
+
+Exit codes:
+  0  success
+  1  verify found a wrong answer, bench found a regression, submit's answer
+     was wrong, or diff-test found implementations that disagree
+  2  bad arguments, an unknown day or part, or a malformed config file
+  3  the input file is missing or unreadable
+  4  the input could not be parsed
+  5  no solution was found
+  6  a part ran for longer than --timeout
+  7  a request to the puzzle site failed";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+  Help,
+  List,
+  All,
+  Verify,
+  New {
+    day: u32,
+  },
+  Fetch {
+    day: u32,
+  },
+  Submit {
+    day: u32,
+    part: u32,
+  },
+  Watch {
+    day: u32,
+  },
+  ClearCache,
+  Gen {
+    day: u32,
+    options: generate::Options,
+  },
+  // Every part with alternatives when `day` is `None`
+  DiffTest {
+    day: Option<u32>,
+    options: difftest::Options,
+  },
+  // Every registered day when `day` is `None`, and every part of it when `part` is
+  Bench {
+    day: Option<u32>,
+    part: Option<u32>,
+    options: bench::Options,
+  },
+  // A whole day when `part` is `None`
+  Run {
+    day: u32,
+    part: Option<u32>,
+  },
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Format {
+  #[default]
+  Text,
+  // One JSON object per line for each part run
+  Json,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Args {
+  pub command: Command,
+  pub input: Option<String>,
+  pub format: Format,
+  // How many days to run at once
+  pub jobs: usize,
+  // How long each part may run before it's cancelled
+  pub timeout: Option<Duration>,
+  // Whether to solve every part rather than reuse cached answers
+  pub no_cache: bool,
+  // Where to write the spans recorded while running days
+  pub trace: Option<String>,
+}
+
+pub fn parse_args<I>(args: I) -> Result<Args, AocError>
+where
+  I: IntoIterator<Item = String>,
+{
+  let mut positional = Vec::new();
+  let mut input = None;
+  let mut format = None;
+  let mut jobs = None;
+  let mut timeout = None;
+  let mut no_cache = false;
+  let mut trace = None;
+  let mut bench = bench::Options::default();
+  // The first bench-only option, which is rejected for other commands
+  let mut bench_flag = None;
+  let mut seed = None;
+  let mut diff = difftest::Options::default();
+  let mut diff_flag = None;
+  let mut gen = generate::Options::default();
+  let mut gen_flag = None;
+  let mut args = args.into_iter();
+  while let Some(arg) = args.next() {
+    // `--flag=value` is the same as `--flag value`
+    let (flag, mut inline) = match arg.find('=') {
+      Some(index) if arg.starts_with("--") => {
+        (&arg[..index], Some(String::from(&arg[index + 1..])))
+      }
+      _ => (arg.as_str(), None),
+    };
+    let mut value = || expect_value(flag, inline.take().or_else(|| args.next()));
+    match flag {
+      "-h" | "--help" => {
+        return Ok(Args {
+          command: Command::Help,
+          input: None,
+          format: Format::Text,
+          jobs: 1,
+          timeout: None,
+          no_cache: false,
+          trace: None,
+        })
+      }
+      "-i" | "--input" => input = Some(value()?),
+      "-t" | "--timeout" => timeout = Some(parse_duration(flag, &value()?)?),
+      "--no-cache" => no_cache = true,
+      "--trace" => trace = Some(value()?),
+      "-j" | "--jobs" => jobs = Some(parse_count(flag, &value()?, 1)? as usize),
+      "--format" => {
+        format = match value()?.as_str() {
+          "text" => Some(Format::Text),
+          "json" => Some(Format::Json),
+          other => {
+            return Err(AocError::InvalidArgument(format!(
+              "expected --format text or json, found {:?}",
+              other
+            )))
+          }
+        }
+      }
+      "--warmup" | "--runs" | "--threshold" | "--save" => {
+        match flag {
+          "--warmup" => bench.warmup = parse_count(flag, &value()?, 0)?,
+          "--runs" => bench.runs = parse_count(flag, &value()?, 1)?,
+          "--threshold" => bench.threshold = parse_percent(flag, &value()?)?,
+          _ => bench.save = true,
+        }
+        bench_flag.get_or_insert_with(|| String::from(flag));
+      }
+      "--seed" => seed = Some(parse_count(flag, &value()?, 0)? as u64),
+      "--cases" => {
+        diff.cases = parse_count(flag, &value()?, 0)? as usize;
+        diff_flag.get_or_insert_with(|| String::from(flag));
+      }
+      "--size" | "--invalid" => {
+        match flag {
+          "--size" => gen.params.size = parse_count(flag, &value()?, 0)? as usize,
+          _ => gen.params.invalid = parse_percent(flag, &value()?)?.min(100.0),
+        }
+        gen_flag.get_or_insert_with(|| String::from(flag));
+      }
+      flag if flag.starts_with('-') && flag.len() > 1 => {
+        return Err(AocError::InvalidArgument(format!(
+          "unknown option {:?}",
+          flag
+        )))
+      }
+      _ => positional.push(arg),
+    }
+  }
+
+  let command = match positional.first().map(String::as_str) {
+    None => {
+      let (last, rest) = COMMANDS.split_last().unwrap();
+      return Err(AocError::InvalidArgument(format!(
+        "expected {} or {}",
+        rest.join(", "),
+        last
+      )));
+    }
+    Some("all") => expect_no_more(&positional, 1, Command::All)?,
+    Some("list") => expect_no_more(&positional, 1, Command::List)?,
+    Some("verify") => expect_no_more(&positional, 1, Command::Verify)?,
+    Some(name @ "new") | Some(name @ "fetch") | Some(name @ "watch") => {
+      let day = match positional.get(1) {
+        Some(day) => parse_number(day, "day")?,
+        None => return Err(AocError::InvalidArgument(format!("{} expects a day", name))),
+      };
+      let command = match name {
+        "new" => Command::New { day },
+        "fetch" => Command::Fetch { day },
+        _ => Command::Watch { day },
+      };
+      expect_no_more(&positional, 2, command)?
+    }
+    Some("cache") => match positional.get(1).map(String::as_str) {
+      Some("clear") => expect_no_more(&positional, 2, Command::ClearCache)?,
+      _ => {
+        return Err(AocError::InvalidArgument(String::from(
+          "expected \"cache clear\"",
+        )))
+      }
+    },
+    Some("diff-test") => {
+      let day = match positional.get(1) {
+        Some(day) => Some(parse_number(day, "day")?),
+        None => None,
+      };
+      if let Some(timeout) = timeout {
+        diff.timeout = timeout;
+      }
+      diff.seed = seed.unwrap_or(diff.seed);
+      let options = std::mem::take(&mut diff);
+      expect_no_more(&positional, 2, Command::DiffTest { day, options })?
+    }
+    Some("gen") => {
+      let day = match positional.get(1) {
+        Some(day) => parse_number(day, "day")?,
+        None => return Err(AocError::InvalidArgument(String::from("gen expects a day"))),
+      };
+      gen.seed = seed.unwrap_or(gen.seed);
+      expect_no_more(&positional, 2, Command::Gen { day, options: gen })?
+    }
+    Some("submit") => {
+      let (day, part) = match (positional.get(1), positional.get(2)) {
+        (Some(day), Some(part)) => (parse_number(day, "day")?, parse_number(part, "part")?),
+        _ => {
+          return Err(AocError::InvalidArgument(String::from(
+            "submit expects a day and a part",
+          )))
+        }
+      };
+      expect_no_more(&positional, 3, Command::Submit { day, part })?
+    }
+    Some("bench") => {
+      let day = match positional.get(1) {
+        Some(day) => Some(parse_number(day, "day")?),
+        None => None,
+      };
+      let part = match positional.get(2) {
+        Some(part) => Some(parse_number(part, "part")?),
+        None => None,
+      };
+      let options = std::mem::take(&mut bench);
+      expect_no_more(&positional, 3, Command::Bench { day, part, options })?
+    }
+    Some(day) => {
+      let day = parse_number(day, "day")?;
+      let part = match positional.get(1) {
+        Some(part) => Some(parse_number(part, "part")?),
+        None => None,
+      };
+      expect_no_more(&positional, 2, Command::Run { day, part })?
+    }
+  };
+  if input.is_some() && !matches!(command, Command::Run { .. } | Command::Watch { .. }) {
+    return Err(AocError::InvalidArgument(String::from(
+      "--input can only be used when running a single day",
+    )));
+  }
+
+  if format.is_some() && !matches!(command, Command::Run { .. } | Command::All) {
+    return Err(AocError::InvalidArgument(String::from(
+      "--format can only be used when running days",
+    )));
+  }
+  let runs_days = matches!(
+    command,
+    Command::Run { .. } | Command::All | Command::Verify
+  );
+  if no_cache && !matches!(command, Command::Run { .. } | Command::All) {
+    return Err(AocError::InvalidArgument(String::from(
+      "--no-cache can only be used when running days",
+    )));
+  }
+  if trace.is_some() && !matches!(command, Command::Run { .. } | Command::All) {
+    return Err(AocError::InvalidArgument(String::from(
+      "--trace can only be used when running days",
+    )));
+  }
+  if jobs.is_some() && !runs_days {
+    return Err(AocError::InvalidArgument(String::from(
+      "--jobs can only be used when running days or verifying",
+    )));
+  }
+  if timeout.is_some() && !TIMEOUT_COMMANDS.iter().any(|(_, takes)| takes(&command)) {
+    let names: Vec<&str> = TIMEOUT_COMMANDS.iter().map(|&(name, _)| name).collect();
+    let (last, rest) = names.split_last().unwrap();
+    return Err(AocError::InvalidArgument(format!(
+      "--timeout can only be used with {} or {}",
+      rest.join(", "),
+      last
+    )));
+  }
+  if seed.is_some() && !matches!(command, Command::Gen { .. } | Command::DiffTest { .. }) {
+    return Err(AocError::InvalidArgument(String::from(
+      "--seed can only be used with gen or diff-test",
+    )));
+  }
+  if let Some(flag) = gen_flag {
+    if !matches!(command, Command::Gen { .. }) {
+      return Err(AocError::InvalidArgument(format!(
+        "{} can only be used with gen",
+        flag
+      )));
+    }
+  }
+  if let Some(flag) = diff_flag {
+    if !matches!(command, Command::DiffTest { .. }) {
+      return Err(AocError::InvalidArgument(format!(
+        "{} can only be used with diff-test",
+        flag
+      )));
+    }
+  }
+  if let Some(flag) = bench_flag {
+    if !matches!(command, Command::Bench { .. }) {
+      return Err(AocError::InvalidArgument(format!(
+        "{} can only be used with bench",
+        flag
+      )));
+    }
+  }
+
+  Ok(Args {
+    command,
+    input,
+    format: format.unwrap_or_default(),
+    jobs: jobs.unwrap_or(1),
+    timeout,
+    no_cache,
+    trace,
+  })
+}
+
+// Whether a flag can be used with a command
+// Every command, as the error for a missing one names them
+const COMMANDS: &[&str] = &[
+  "a day",
+  "all",
+  "list",
+  "verify",
+  "new",
+  "fetch",
+  "submit",
+  "watch",
+  "bench",
+  "cache clear",
+  "diff-test",
+  "gen",
+];
+
+type Takes = fn(&Command) -> bool;
+
+// The commands that take --timeout, with how its error message names them
+const TIMEOUT_COMMANDS: &[(&str, Takes)] = &[
+  ("a day or all", |command| {
+    matches!(command, Command::Run { .. } | Command::All)
+  }),
+  ("verify", |command| matches!(command, Command::Verify)),
+  ("watch", |command| matches!(command, Command::Watch { .. })),
+  ("diff-test", |command| {
+    matches!(command, Command::DiffTest { .. })
+  }),
+];
+
+fn expect_value(flag: &str, value: Option<String>) -> Result<String, AocError> {
+  value.ok_or_else(|| AocError::InvalidArgument(format!("{} expects a value", flag)))
+}
+
+fn parse_number(arg: &str, name: &str) -> Result<u32, AocError> {
+  arg
+    .parse()
+    .map_err(|_| AocError::InvalidArgument(format!("expected a {} number, found {:?}", name, arg)))
+}
+
+fn parse_count(flag: &str, arg: &str, min: u32) -> Result<u32, AocError> {
+  match arg.parse() {
+    Ok(count) if count >= min => Ok(count),
+    _ => Err(AocError::InvalidArgument(format!(
+      "{} expects a whole number of at least {}, found {:?}",
+      flag, min, arg
+    ))),
+  }
+}
+
+// A positive number of seconds, or of milliseconds with an "ms" suffix, e.g. 2.5s or 500ms
+fn parse_duration(flag: &str, arg: &str) -> Result<Duration, AocError> {
+  let (number, scale) = match arg.strip_suffix("ms") {
+    Some(millis) => (millis, 1e-3),
+    None => (arg.strip_suffix('s').unwrap_or(arg), 1.0),
+  };
+  let invalid = || {
+    AocError::InvalidArgument(format!(
+      "{} expects a duration like 5s or 500ms, found {:?}",
+      flag, arg
+    ))
+  };
+  match number.parse::<f64>() {
+    Ok(n) if n > 0.0 && n.is_finite() => {
+      Duration::try_from_secs_f64(n * scale).map_err(|_| invalid())
+    }
+    _ => Err(invalid()),
+  }
+}
+
+fn parse_percent(flag: &str, arg: &str) -> Result<f64, AocError> {
+  match arg.trim_end_matches('%').parse::<f64>() {
+    Ok(percent) if percent >= 0.0 && percent.is_finite() => Ok(percent),
+    _ => Err(AocError::InvalidArgument(format!(
+      "{} expects a percentage, found {:?}",
+      flag, arg
+    ))),
+  }
+}
+
+fn expect_no_more(
+  positional: &[String],
+  used: usize,
+  command: Command,
+) -> Result<Command, AocError> {
+  match positional.get(used) {
+    Some(extra) => Err(AocError::InvalidArgument(format!(
+      "unexpected argument {:?}",
+      extra
+    ))),
+    None => Ok(command),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(args: &[&str]) -> Result<Command, AocError> {
+    parse_args(args.iter().map(|arg| String::from(*arg))).map(|args| args.command)
+  }
+
+  #[test]
+  fn test_commands() {
+    assert_eq!(Ok(Command::All), parse(&["all"]));
+    assert_eq!(Ok(Command::List), parse(&["list"]));
+    assert_eq!(Ok(Command::Verify), parse(&["verify"]));
+    assert_eq!(Ok(Command::New { day: 15 }), parse(&["new", "15"]));
+    assert_eq!(Ok(Command::Fetch { day: 15 }), parse(&["fetch", "15"]));
+    assert_eq!(Ok(Command::Watch { day: 15 }), parse(&["watch", "15"]));
+    assert_eq!(Ok(Command::ClearCache), parse(&["cache", "clear"]));
+    assert_eq!(
+      Ok(Command::DiffTest {
+        day: None,
+        options: difftest::Options::default()
+      }),
+      parse(&["diff-test"])
+    );
+    assert_eq!(
+      Ok(Command::Submit { day: 15, part: 2 }),
+      parse(&["submit", "15", "2"])
+    );
+    assert_eq!(
+      Ok(Command::Bench {
+        day: None,
+        part: None,
+        options: bench::Options::default()
+      }),
+      parse(&["bench"])
+    );
+    assert_eq!(Ok(Command::Help), parse(&["3", "--help"]));
+    assert_eq!(Ok(Command::Run { day: 3, part: None }), parse(&["3"]));
+    assert_eq!(
+      Ok(Command::Run {
+        day: 3,
+        part: Some(2)
+      }),
+      parse(&["3", "2"])
+    );
+  }
+
+  #[test]
+  fn test_input() {
+    let args = |list: &[&str]| parse_args(list.iter().map(|arg| String::from(*arg)));
+    assert_eq!(
+      Some(String::from("input/day12-alt.txt")),
+      args(&["12", "2", "--input", "input/day12-alt.txt"])
+        .unwrap()
+        .input
+    );
+    assert_eq!(
+      Some(String::from("-")),
+      args(&["-i", "-", "12"]).unwrap().input
+    );
+    assert_eq!(
+      Some(String::from("x.txt")),
+      args(&["--input=x.txt", "3"]).unwrap().input
+    );
+    assert!(args(&["all", "--input", "x.txt"]).is_err());
+    assert!(args(&["watch", "3", "--input", "x.txt"]).is_ok());
+    assert!(args(&["3", "--input"]).is_err());
+  }
+
+  #[test]
+  fn test_format() {
+    let args = |list: &[&str]| parse_args(list.iter().map(|arg| String::from(*arg)));
+    assert_eq!(Format::Text, args(&["all"]).unwrap().format);
+    assert_eq!(
+      Format::Json,
+      args(&["all", "--format", "json"]).unwrap().format
+    );
+    assert_eq!(
+      Format::Json,
+      args(&["--format=json", "3", "1"]).unwrap().format
+    );
+    assert!(args(&["all", "--format", "xml"]).is_err());
+    assert!(args(&["list", "--format", "json"]).is_err());
+  }
+
+  #[test]
+  fn test_jobs() {
+    let args = |list: &[&str]| parse_args(list.iter().map(|arg| String::from(*arg)));
+    assert_eq!(1, args(&["all"]).unwrap().jobs);
+    assert_eq!(4, args(&["all", "-j", "4"]).unwrap().jobs);
+    assert_eq!(2, args(&["verify", "--jobs=2"]).unwrap().jobs);
+    assert!(args(&["all", "--jobs", "0"]).is_err());
+    assert!(args(&["bench", "--jobs", "2"]).is_err());
+  }
+
+  #[test]
+  fn test_timeout() {
+    let args = |list: &[&str]| parse_args(list.iter().map(|arg| String::from(*arg)));
+    assert_eq!(None, args(&["all"]).unwrap().timeout);
+    for (arg, millis) in &[("2", 2000), ("2.5s", 2500), ("500ms", 500)] {
+      let timeout = args(&["all", "--timeout", arg]).unwrap().timeout;
+      assert_eq!(Some(Duration::from_millis(*millis)), timeout, "{}", arg);
+    }
+    for arg in &["0", "-1s", "soon", "5m", "1e20", "1e30ms"] {
+      match args(&["all", "--timeout", arg]) {
+        Err(AocError::InvalidArgument(_)) => {}
+        other => panic!("expected --timeout {} to be rejected, got {:?}", arg, other),
+      }
+    }
+    assert_eq!(
+      Err(AocError::InvalidArgument(String::from(
+        "--timeout can only be used with a day or all, verify, watch or diff-test"
+      ))),
+      args(&["bench", "-t", "1s"]).map(|_| ())
+    );
+    assert!(args(&["watch", "3", "-t", "1s"]).is_ok());
+    assert!(args(&["diff-test", "-t", "1s"]).is_ok());
+  }
+
+  #[test]
+  fn test_no_cache() {
+    let args = |list: &[&str]| parse_args(list.iter().map(|arg| String::from(*arg)));
+    assert!(!args(&["all"]).unwrap().no_cache);
+    assert!(args(&["all", "--no-cache"]).unwrap().no_cache);
+    assert!(args(&["--no-cache", "3", "1"]).unwrap().no_cache);
+    assert!(args(&["verify", "--no-cache"]).is_err());
+    assert!(args(&["cache", "clear", "--no-cache"]).is_err());
+  }
+
+  #[test]
+  fn test_trace() {
+    let args = |list: &[&str]| parse_args(list.iter().map(|arg| String::from(*arg)));
+    assert_eq!(None, args(&["all"]).unwrap().trace);
+    assert_eq!(
+      Some(String::from("trace.json")),
+      args(&["11", "--trace", "trace.json"]).unwrap().trace
+    );
+    assert_eq!(
+      Some(String::from("day10.folded")),
+      args(&["--trace=day10.folded", "10", "2"]).unwrap().trace
+    );
+    assert!(args(&["11", "--trace"]).is_err());
+    assert!(args(&["verify", "--trace", "trace.json"]).is_err());
+  }
+
+  #[test]
+  fn test_diff_test_options() {
+    assert_eq!(
+      Ok(Command::DiffTest {
+        day: Some(13),
+        options: difftest::Options {
+          seed: 7,
+          cases: 0,
+          timeout: Duration::from_millis(50),
+        }
+      }),
+      parse(&["diff-test", "13", "--seed=7", "--cases", "0", "-t", "50ms"])
+    );
+    assert!(parse(&["13", "--seed", "7"]).is_err());
+    assert!(parse(&["diff-test", "--cases", "-1"]).is_err());
+  }
+
+  #[test]
+  fn test_gen_options() {
+    assert_eq!(
+      Ok(Command::Gen {
+        day: 4,
+        options: generate::Options::default()
+      }),
+      parse(&["gen", "4"])
+    );
+    assert_eq!(
+      Ok(Command::Gen {
+        day: 4,
+        options: generate::Options {
+          seed: 9,
+          params: generate::Params {
+            size: 1000,
+            invalid: 40.0,
+          },
+        }
+      }),
+      parse(&["gen", "4", "--seed", "9", "--size=1000", "--invalid", "40%"])
+    );
+    assert!(parse(&["diff-test", "--size", "10"]).is_err());
+    assert!(parse(&["gen", "4", "--cases", "10"]).is_err());
+    assert!(parse(&["verify", "--seed", "1"]).is_err());
+    assert!(parse(&["gen", "4", "--invalid", "x"]).is_err());
+  }
+
+  #[test]
+  fn test_bench_options() {
+    assert_eq!(
+      Ok(Command::Bench {
+        day: Some(11),
+        part: Some(1),
+        options: bench::Options {
+          warmup: 0,
+          runs: 5,
+          threshold: 2.5,
+          save: true,
+        }
+      }),
+      parse(&[
+        "--runs=5",
+        "bench",
+        "11",
+        "1",
+        "--warmup",
+        "0",
+        "--threshold",
+        "2.5%",
+        "--save"
+      ])
+    );
+  }
+
+  #[test]
+  fn test_missing_command() {
+    assert_eq!(
+      Err(AocError::InvalidArgument(String::from(
+        "expected a day, all, list, verify, new, fetch, submit, watch, bench, cache clear, \
+         diff-test or gen"
+      ))),
+      parse(&[]).map(|_| ())
+    );
+    // Every command in the usage text is named in that error
+    let commands = USAGE
+      .split("Commands:\n")
+      .nth(1)
+      .unwrap()
+      .split("\n\n")
+      .next();
+    for line in commands.unwrap().lines() {
+      let name = match line.strip_prefix("  ") {
+        Some(name) if !name.starts_with(' ') && !name.starts_with('<') => name,
+        _ => continue,
+      };
+      let name = name.split(&['<', '['][..]).next().unwrap();
+      let name = name.split("  ").next().unwrap().trim_end();
+      assert!(
+        COMMANDS.contains(&name),
+        "{:?} is missing from COMMANDS",
+        name
+      );
+    }
+  }
+
+  #[test]
+  fn test_bad_arguments() {
+    for args in &[
+      &[][..],
+      &["x"],
+      &["3", "y"],
+      &["3", "2", "1"],
+      &["all", "3"],
+      &["verify", "3"],
+      &["new"],
+      &["new", "15", "1"],
+      &["fetch"],
+      &["cache"],
+      &["cache", "list"],
+      &["cache", "clear", "3"],
+      &["submit", "15"],
+      &["submit", "15", "1", "2"],
+      &["bench", "3", "1", "2"],
+      &["bench", "x"],
+      &["diff-test", "13", "2"],
+      &["gen"],
+      &["gen", "4", "1"],
+      &["bench", "--runs", "0"],
+      &["bench", "--threshold", "fast"],
+      &["all", "--save"],
+      &["3", "--runs=5"],
+      &["--nope"],
+    ] {
+      match parse(args) {
+        Err(AocError::InvalidArgument(_)) => {}
+        other => panic!("expected {:?} to be rejected, got {:?}", args, other),
+      }
+    }
+  }
+}
@@ -0,0 +1,95 @@
+use crate::kv;
+use crate::AocError;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// The repository root, where `input/` and the optional `aoc.toml` live
+pub const ROOT: &str = env!("CARGO_MANIFEST_DIR");
+
+// Settings from `aoc.toml` (or the file named by `AOC_CONFIG`). Every setting is optional.
+#[derive(Debug, Default, PartialEq)]
+pub struct Config {
+  // Relative paths are resolved against the config file's directory
+  pub input_dir: Option<PathBuf>,
+  // The known answers checked by `verify`, instead of answers.toml
+  pub answers: Option<PathBuf>,
+  // The benchmark baseline used by `bench`, instead of bench.toml
+  pub baseline: Option<PathBuf>,
+  // The submission history used by `submit`, instead of submissions.toml
+  pub history: Option<PathBuf>,
+  // The answers cached by running days, instead of cache.toml
+  pub cache: Option<PathBuf>,
+  // The puzzle site's session cookie, for `fetch` and `submit`
+  pub session: Option<String>,
+  // Where the puzzle site is, to test against a stub server
+  pub base_url: Option<String>,
+}
+
+impl Config {
+  pub fn load() -> Result<Config, AocError> {
+    let path = match env::var_os("AOC_CONFIG") {
+      Some(path) => PathBuf::from(path),
+      None => {
+        let path = Path::new(ROOT).join("aoc.toml");
+        if !path.exists() {
+          return Ok(Config::default());
+        }
+        path
+      }
+    };
+    let text =
+      fs::read_to_string(&path).map_err(|e| AocError::Io(format!("{}: {}", path.display(), e)))?;
+    Config::parse(&text, path.parent().unwrap_or_else(|| Path::new(".")))
+      .map_err(|err| err.in_file(&path))
+  }
+
+  pub fn parse(text: &str, dir: &Path) -> Result<Config, AocError> {
+    let mut config = Config::default();
+    for entry in kv::parse(text)? {
+      match (entry.section.as_str(), entry.key.as_str()) {
+        ("", "input_dir") => config.input_dir = Some(dir.join(entry.value)),
+        ("", "answers") => config.answers = Some(dir.join(entry.value)),
+        ("", "baseline") => config.baseline = Some(dir.join(entry.value)),
+        ("", "history") => config.history = Some(dir.join(entry.value)),
+        ("", "cache") => config.cache = Some(dir.join(entry.value)),
+        ("", "session") => config.session = Some(entry.value),
+        ("", "base_url") => config.base_url = Some(entry.value),
+        _ => {
+          return Err(AocError::InvalidArgument(format!(
+            "unknown config setting {:?} on line {}",
+            entry.key, entry.line
+          )))
+        }
+      }
+    }
+    Ok(config)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse() {
+    let config = Config::parse("input_dir = \"alt\"\n", Path::new("/repo")).unwrap();
+    assert_eq!(Some(PathBuf::from("/repo/alt")), config.input_dir);
+
+    let config = Config::parse("input_dir = \"/abs\"\n", Path::new("/repo")).unwrap();
+    assert_eq!(Some(PathBuf::from("/abs")), config.input_dir);
+
+    let config = Config::parse("answers = \"mine.toml\"\n", Path::new("/repo")).unwrap();
+    assert_eq!(Some(PathBuf::from("/repo/mine.toml")), config.answers);
+
+    let config = Config::parse(
+      "session = \"53616c\"\nbase_url = \"http://localhost:8080\"\n",
+      Path::new("/repo"),
+    )
+    .unwrap();
+    assert_eq!(Some(String::from("53616c")), config.session);
+    assert_eq!(Some(String::from("http://localhost:8080")), config.base_url);
+
+    assert!(Config::parse("nope = 1\n", Path::new("/repo")).is_err());
+  }
+}
@@ -1,35 +1,155 @@
+use crate::error::parse_number;
+use crate::generate::{self, Params};
+use crate::input::Input;
+use crate::rng::Rng;
+use crate::{Answer, AocError, Day};
 use std::collections::BTreeSet;
-use std::error::Error;
 
-pub fn part1(input: &str) -> Result<i64, Box<dyn Error>> {
+fn parse_input(input: &Input) -> Result<Vec<i64>, AocError> {
+    input.parse_lines(|line| parse_number(line, line, "an expense"))
+}
+
+// The expense report, one entry per line
+pub fn parse(input: &str) -> Result<Vec<i64>, AocError> {
+    parse_input(&Input::new(input))
+}
+
+pub fn solve_part1(expenses: &[i64]) -> Result<i64, AocError> {
     let mut set = BTreeSet::new();
-    for line in input.lines() {
-        let val = str::parse::<i64>(line)?;
+    for &val in expenses {
         set.insert(val);
-        if set.contains(&(2020 - val)) {
-            return Ok(val * (2020 - val));
+        // Nothing can pair with an expense so negative that its partner is past i64::MAX
+        let partner = match 2020i64.checked_sub(val) {
+            Some(partner) => partner,
+            None => continue,
+        };
+        if set.contains(&partner) {
+            return val.checked_mul(partner).ok_or_else(|| {
+                AocError::NoSolution(String::from("the product of the pair overflowed"))
+            });
         }
     }
-    return Err("Match not found")?;
+    Err(AocError::NoSolution(String::from(
+        "no two expenses sum to 2020",
+    )))
 }
 
-pub fn part2(input: &str) -> Result<i64, Box<dyn Error>> {
-    let mut set = BTreeSet::new();
-    for line in input.lines() {
-        let val = str::parse::<i64>(line)?;
-        set.insert(val);
-    }
+pub fn solve_part2(expenses: &[i64]) -> Result<i64, AocError> {
+    let set: BTreeSet<i64> = expenses.iter().copied().collect();
     // This will be sorted
     for low in set.range(0..) {
         for next in set.range(low..) {
-            let set_match = 2020 - low - next;
-            if set_match < 0 {
-                break;
-            }
+            let set_match = match (2020 - low).checked_sub(*next) {
+                Some(set_match) if set_match >= 0 => set_match,
+                _ => break,
+            };
             if set.contains(&set_match) {
-                return Ok((low * next * set_match) as i64);
+                return Ok(low * next * set_match);
+            }
+        }
+    }
+    Err(AocError::NoSolution(String::from(
+        "no three expenses sum to 2020",
+    )))
+}
+
+pub struct Day1;
+
+impl Day for Day1 {
+    type Model = Vec<i64>;
+    const SOURCE: &'static str = include_str!("day1.rs");
+
+    fn day(&self) -> u32 {
+        1
+    }
+
+    fn title(&self) -> &'static str {
+        "Report Repair"
+    }
+
+    fn parse(&self, input: &Input) -> Result<Self::Model, AocError> {
+        parse_input(input)
+    }
+
+    fn part1(&self, model: &Self::Model) -> Result<Answer, AocError> {
+        Ok(solve_part1(model)?.into())
+    }
+
+    fn part2(&self, model: &Self::Model) -> Result<Answer, AocError> {
+        Ok(solve_part2(model)?.into())
+    }
+
+    // `size` distinct expenses (5 to 800) with exactly one pair and one triple summing
+    // to 2020. Apart from the pair's larger half, the filler is all above 1010, so
+    // fillers can't pair up with each other and only sum to 2020 with small entries
+    // that are avoided
+    fn generate(&self, rng: &mut Rng, params: &Params) -> Option<String> {
+        let (small, pair) = loop {
+            let (a, b) = (rng.range(1, 1009), rng.range(1, 1009));
+            let triple = [a, b, 2020 - a - b];
+            let other = rng.range(1, 1009);
+            let small = [triple[0], triple[1], triple[2], other];
+            let pair = 2020 - other;
+            if (1..=1009).contains(&triple[2]) && sums(&small, pair) == (1, 1) {
+                break (small, pair);
             }
+        };
+        let mut expenses = small.to_vec();
+        expenses.push(pair);
+        // Any filler that would make a second pair or triple with the small entries
+        let taken: BTreeSet<i64> = small
+            .iter()
+            .flat_map(|&x| small.iter().map(move |&y| 2020 - x - y))
+            .chain(small.iter().map(|&x| 2020 - x))
+            .chain(expenses.iter().copied())
+            .collect();
+        let mut filler: Vec<i64> = (1011..2020).filter(|x| !taken.contains(x)).collect();
+        rng.shuffle(&mut filler);
+        expenses.extend(filler.iter().take(params.size.clamp(5, 800) - 5));
+        rng.shuffle(&mut expenses);
+        Some(generate::lines(&expenses))
+    }
+}
+
+// How many pairs and triples of `small` and `large` sum to 2020, allowing an entry to
+// be used twice like `solve_part2` does
+fn sums(small: &[i64], large: i64) -> (usize, usize) {
+    let mut all = small.to_vec();
+    all.push(large);
+    all.sort_unstable();
+    all.dedup();
+    if all.len() != small.len() + 1 {
+        return (0, 0);
+    }
+    let mut pairs = 0;
+    let mut triples = 0;
+    for (i, &x) in all.iter().enumerate() {
+        for (j, &y) in all.iter().enumerate().skip(i) {
+            if j > i && x + y == 2020 {
+                pairs += 1;
+            }
+            triples += all[j..].iter().filter(|&&z| x + y + z == 2020).count();
         }
     }
-    return Err("Match not found")?;
+    (pairs, triples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overflow() {
+        let expenses = parse("-9223372036854775808\n9223372036854775807\n2019\n1").unwrap();
+        assert_eq!(vec![i64::MIN, i64::MAX, 2019, 1], expenses);
+        assert_eq!(Ok(2019), solve_part1(&expenses));
+        assert!(matches!(
+            solve_part2(&expenses),
+            Err(AocError::NoSolution(_))
+        ));
+        assert!(matches!(
+            solve_part1(&[-(1 << 40), 2020 + (1 << 40)]),
+            Err(AocError::NoSolution(_))
+        ));
+    }
 }
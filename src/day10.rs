@@ -1,8 +1,26 @@
+use crate::cancel;
+use crate::error::parse_number;
+use crate::generate::{self, Params};
+use crate::input::Input;
+use crate::rng::Rng;
+use crate::trace;
+use crate::{Alternative, Answer, AocError, Day};
 use std::collections::HashMap;
-use std::error::Error;
 
-fn parse_part_1(input: &str) -> i64 {
-  let mut numbers: Vec<i64> = input.lines().map(|x| x.parse().unwrap()).collect();
+fn parse_input(input: &Input) -> Result<Vec<i64>, AocError> {
+  input.parse_lines(|line| match parse_number(line, line, "a joltage")? {
+    joltage if joltage < 0 => Err(AocError::parse(line, line, "joltages can't be negative")),
+    joltage => Ok(joltage),
+  })
+}
+
+// The adapters' joltages, in the order they're listed
+pub fn parse(input: &str) -> Result<Vec<i64>, AocError> {
+  parse_input(&Input::new(input))
+}
+
+pub fn solve_part1(adapters: &[i64]) -> Result<i64, AocError> {
+  let mut numbers = adapters.to_vec();
   numbers.sort();
 
   let mut one_difference = 0;
@@ -19,111 +37,214 @@ fn parse_part_1(input: &str) -> i64 {
     }
   }
 
-  one_difference * three_difference
-}
-
-pub fn part1(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(parse_part_1(input) as i64)
+  Ok(one_difference * three_difference)
 }
 
-pub fn possibilities(sorted: &[i64], cache: &mut HashMap<i64, i64>) -> i64 {
-  if let Some(&val) = cache.get(&sorted[0]) {
-    return val;
-  }
+pub fn possibilities(sorted: &[i64], cache: &mut HashMap<i64, u128>) -> Result<u128, AocError> {
+  let _span = trace::span("day10::possibilities");
   let len = sorted.len();
   if len == 0 {
-    return 0;
+    return Ok(0);
   }
   let number = sorted[0];
-  let mut total = 0;
+  if let Some(&val) = cache.get(&number) {
+    trace::count("cache hits");
+    return Ok(val);
+  }
+  cancel::check()?;
+  // The last adapter can only be arranged one way
+  if len == 1 {
+    return Ok(1);
+  }
+  let mut total: u128 = 0;
   let mut i = 1;
   loop {
     if sorted[i] - number > 3 {
       cache.insert(number, total);
-      return total;
+      return Ok(total);
     }
     if len == i + 1 {
-      cache.insert(number, total + 1);
-      return total + 1;
+      let total = add_arrangements(total, 1)?;
+      cache.insert(number, total);
+      return Ok(total);
     }
-    total += possibilities(&sorted[i..], cache);
+    total = add_arrangements(total, possibilities(&sorted[i..], cache)?)?;
     i += 1;
   }
 }
 
-pub fn parse_part_2(input: &str) -> i64 {
-  let mut numbers: Vec<i64> = input.lines().map(|x| x.parse().unwrap()).collect();
+// Even a u128 runs out after about 150 adapters
+fn add_arrangements(a: u128, b: u128) -> Result<u128, AocError> {
+  a.checked_add(b)
+    .ok_or_else(|| AocError::NoSolution(String::from("there are too many arrangements to count")))
+}
+
+pub fn solve_part2(adapters: &[i64]) -> Result<u128, AocError> {
+  let mut numbers = adapters.to_vec();
   // Prepare - adding 0 to the beginning, sorting, and adding max + 3 to the end
   numbers.push(0);
   numbers.sort();
-  numbers.push(numbers[numbers.len() - 1] + 3);
+  numbers.push(device(&numbers)?);
 
   let mut cache = HashMap::new();
 
   possibilities(&numbers, &mut cache)
 }
 
-pub fn part2(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(parse_part_2(input) as i64)
+// The device's joltage, 3 above the highest adapter
+fn device(sorted: &[i64]) -> Result<i64, AocError> {
+  sorted
+    .last()
+    .map_or(Some(3), |highest| highest.checked_add(3))
+    .ok_or_else(|| AocError::NoSolution(String::from("the device's joltage overflowed")))
+}
+
+// Counts the arrangements ending at each adapter in turn, from the ones up to 3 jolts
+// below it
+fn part_2_dynamic(adapters: &[i64]) -> Result<u128, AocError> {
+  let mut numbers = adapters.to_vec();
+  numbers.push(0);
+  numbers.sort();
+  let mut ways: Vec<u128> = vec![1];
+  for i in 1..numbers.len() {
+    let total = (0..i)
+      .filter(|&j| numbers[i] - numbers[j] <= 3)
+      .try_fold(0, |total, j| add_arrangements(total, ways[j]))?;
+    ways.push(total);
+  }
+  Ok(ways[ways.len() - 1])
+}
+
+pub struct Day10;
+
+impl Day for Day10 {
+  type Model = Vec<i64>;
+  const SOURCE: &'static str = include_str!("day10.rs");
+
+  fn day(&self) -> u32 {
+    10
+  }
+
+  fn title(&self) -> &'static str {
+    "Adapter Array"
+  }
+
+  fn parse(&self, input: &Input) -> Result<Self::Model, AocError> {
+    parse_input(input)
+  }
+
+  fn part1(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part1(model)?.into())
+  }
+
+  fn part2(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part2(model)?.into())
+  }
+
+  fn alternatives(&self) -> Vec<Alternative<Self::Model>> {
+    vec![Alternative {
+      part: 2,
+      name: "dynamic programming",
+      solve: |adapters| Ok(part_2_dynamic(adapters)?.into()),
+    }]
+  }
+
+  // `size` distinct adapters, each 1 to 3 jolts above the one before, shuffled. Fewer
+  // once the number of arrangements would no longer fit in a u128.
+  fn generate(&self, rng: &mut Rng, params: &Params) -> Option<String> {
+    // Each joltage so far, from 0, with the number of ways of reaching it
+    let mut ways: Vec<(i64, u128)> = vec![(0, 1)];
+    let mut adapters = Vec::new();
+    while adapters.len() < params.size.max(1) {
+      let joltage = ways[ways.len() - 1].0 + rng.range(1, 3);
+      let total = ways
+        .iter()
+        .rev()
+        .take_while(|&&(below, _)| joltage - below <= 3)
+        .try_fold(0u128, |total, &(_, n)| total.checked_add(n));
+      match total {
+        Some(total) => ways.push((joltage, total)),
+        None => break,
+      }
+      adapters.push(joltage);
+    }
+    rng.shuffle(&mut adapters);
+    Some(generate::lines(&adapters))
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::examples::Example;
+  use crate::property::{self, Ints, Vecs};
 
-  static INPUT_EXAMPLE_1: &str = r"16
-10
-15
-5
-1
-11
-7
-19
-6
-12
-4";
-
-  static INPUT_EXAMPLE_2: &str = r"28
-33
-18
-42
-31
-14
-46
-20
-48
-47
-24
-23
-49
-45
-19
-38
-39
-11
-1
-32
-25
-35
-8
-17
-7
-9
-4
-2
-34
-10
-3";
+  #[test]
+  fn test_possibilities() {
+    let mut adapters = parse(&Example::load(10, "1").unwrap().input).unwrap();
+    adapters.push(0);
+    adapters.sort();
+    assert_eq!(Ok(8), possibilities(&adapters, &mut HashMap::new()));
+    assert_eq!(Ok(1), possibilities(&[22], &mut HashMap::new()));
+    assert_eq!(Ok(0), possibilities(&[], &mut HashMap::new()));
+  }
 
+  // 120 adapters one jolt apart overflow a u64, but not a u128
   #[test]
-  fn test_part1() {
-    assert_eq!(7 * 5, parse_part_1(INPUT_EXAMPLE_1));
-    assert_eq!(22 * 10, parse_part_1(INPUT_EXAMPLE_2));
+  fn test_big_answers() {
+    let adapters: Vec<i64> = (1..=120).collect();
+    let answer = Day10.part2(&adapters).unwrap();
+    assert!(matches!(answer, Answer::BigInt(_)), "{:?}", answer);
+    assert_eq!(Ok(answer), (Day10.alternatives()[0].solve)(&adapters));
+    let adapters: Vec<i64> = (1..=200).collect();
+    assert!(matches!(
+      solve_part2(&adapters),
+      Err(AocError::NoSolution(_))
+    ));
+    assert!(matches!(
+      solve_part2(&[i64::MAX]),
+      Err(AocError::NoSolution(_))
+    ));
+    assert!(parse("3\n-1").is_err());
   }
 
+  // `possibilities` agrees with trying every subset of a few adapters
   #[test]
-  fn test_part2() {
-    assert_eq!(8, parse_part_2(INPUT_EXAMPLE_1));
-    assert_eq!(19208, parse_part_2(INPUT_EXAMPLE_2));
+  fn test_possibilities_exhaustive() {
+    let adapters = Vecs {
+      item: Ints { low: 1, high: 20 },
+      min: 0,
+      max: 10,
+    };
+    property::check(&adapters, |adapters| {
+      let mut adapters = adapters.clone();
+      adapters.sort_unstable();
+      adapters.dedup();
+      let device = adapters.last().unwrap_or(&0) + 3;
+      let exhaustive = (0..1u32 << adapters.len())
+        .filter(|used| {
+          let mut joltage = 0;
+          for (i, &adapter) in adapters.iter().enumerate() {
+            if used & 1 << i != 0 {
+              if adapter - joltage > 3 {
+                return false;
+              }
+              joltage = adapter;
+            }
+          }
+          device - joltage <= 3
+        })
+        .count() as u128;
+      let mut chain = vec![0];
+      chain.extend(&adapters);
+      chain.push(device);
+      match possibilities(&chain, &mut HashMap::new()) {
+        Ok(counted) if counted == exhaustive => Ok(()),
+        counted => Err(format!(
+          "possibilities counted {:?}, but there are {}",
+          counted, exhaustive
+        )),
+      }
+    });
   }
 }
@@ -1,5 +1,10 @@
+use crate::cancel;
+use crate::generate::{self, Params};
+use crate::input::Input;
+use crate::rng::Rng;
+use crate::trace;
+use crate::{Answer, AocError, Day};
 use std::collections::HashMap;
-use std::error::Error;
 use std::fmt;
 
 static DIRECTIONS: [(i64, i64); 8] = [
@@ -13,45 +18,57 @@ static DIRECTIONS: [(i64, i64); 8] = [
   (1, 1),
 ];
 
-// idx = col + row * rows
-#[derive(Copy, Clone, PartialEq, Eq)]
-enum Position {
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Position {
   Floor,
   Occupied,
   Empty,
 }
 
-struct Grid {
+// The seating area, one `Position` per cell
+// idx = col + row * cols
+#[derive(Clone)]
+pub struct Grid {
   map: HashMap<usize, Position>,
   rows: usize,
   cols: usize,
 }
 
 impl Grid {
-  fn new(input: &str) -> Grid {
-    let lines: Vec<&str> = input.lines().collect();
-    let rows = lines.len();
-    let cols = lines[0].len();
+  fn new(input: &Input) -> Result<Grid, AocError> {
+    let cells = input.grid(".L#")?;
+    let rows = cells.len();
+    let cols = cells[0].len();
     let mut map = HashMap::new();
-    for (row, line) in lines.iter().enumerate() {
-      for (col, c) in line.chars().enumerate() {
+    for (row, line) in cells.iter().enumerate() {
+      for (col, c) in line.iter().enumerate() {
         let position = match c {
           '.' => Position::Floor,
           'L' => Position::Empty,
-          '#' => Position::Occupied,
-          _ => panic!("Invalid position given"),
+          _ => Position::Occupied,
         };
-        map.insert(col + row * rows, position);
+        map.insert(col + row * cols, position);
       }
     }
-    Grid { map, rows, cols }
+    Ok(Grid { map, rows, cols })
+  }
+
+  pub fn rows(&self) -> usize {
+    self.rows
+  }
+
+  pub fn cols(&self) -> usize {
+    self.cols
   }
 
-  fn get(&self, row: usize, col: usize) -> Option<&Position> {
-    self.map.get(&(col + row * self.rows))
+  pub fn get(&self, row: usize, col: usize) -> Option<&Position> {
+    if col >= self.cols {
+      return None;
+    }
+    self.map.get(&(col + row * self.cols))
   }
 
-  fn count_occupied(&self) -> u64 {
+  pub fn count_occupied(&self) -> u64 {
     let mut total = 0;
     for row in 0..self.rows {
       for col in 0..self.cols {
@@ -65,11 +82,7 @@ impl Grid {
   }
 
   fn is_occupied(&self, row: usize, col: usize) -> bool {
-    if let Some(Position::Occupied) = self.get(row, col) {
-      true
-    } else {
-      false
-    }
+    matches!(self.get(row, col), Some(Position::Occupied))
   }
 
   fn has_far_occupied_adjacent(&self, row: usize, col: usize, row_mod: i64, col_mod: i64) -> bool {
@@ -97,7 +110,7 @@ impl Grid {
       if test_col < 0
         || test_row < 0
         || test_row >= self.rows as i64
-        || test_col >= self.rows as i64
+        || test_col >= self.cols as i64
       {
         continue;
       }
@@ -121,7 +134,9 @@ impl Grid {
     total
   }
 
-  fn mutate(&self) -> Grid {
+  // The next round, where seats only look at their neighbours
+  pub fn mutate(&self) -> Grid {
+    let _span = trace::span("day11::mutate");
     let mut map: HashMap<usize, Position> = HashMap::new();
     let cols = self.cols;
     let rows = self.rows;
@@ -136,13 +151,15 @@ impl Grid {
             position = Position::Empty;
           }
         }
-        map.insert(col + row * rows, position);
+        map.insert(col + row * cols, position);
       }
     }
     Grid { map, rows, cols }
   }
 
-  fn mutate_far(&self) -> Grid {
+  // The next round, where seats look at the first seat in each direction
+  pub fn mutate_far(&self) -> Grid {
+    let _span = trace::span("day11::mutate_far");
     let mut map: HashMap<usize, Position> = HashMap::new();
     let cols = self.cols;
     let rows = self.rows;
@@ -157,7 +174,7 @@ impl Grid {
             position = Position::Empty;
           }
         }
-        map.insert(col + row * rows, position);
+        map.insert(col + row * cols, position);
       }
     }
     Grid { map, rows, cols }
@@ -198,67 +215,150 @@ impl fmt::Display for Grid {
   }
 }
 
-fn parse_part_1(input: &str) -> i64 {
-  let mut grid = Grid::new(input);
+pub fn parse(input: &str) -> Result<Grid, AocError> {
+  Grid::new(&Input::new(input))
+}
+
+// The occupied seats once the neighbour rules stop changing anything
+pub fn solve_part1(grid: &Grid) -> Result<u64, AocError> {
+  let mut grid = grid.clone();
   loop {
+    cancel::check()?;
     let new_grid = grid.mutate();
     if new_grid == grid {
-      return grid.count_occupied() as i64;
+      return Ok(grid.count_occupied());
     }
     grid = new_grid;
   }
 }
 
-fn parse_part_2(input: &str) -> i64 {
-  let mut grid = Grid::new(input);
+// The occupied seats once the line-of-sight rules stop changing anything
+pub fn solve_part2(grid: &Grid) -> Result<u64, AocError> {
+  let mut grid = grid.clone();
   loop {
+    cancel::check()?;
     let new_grid = grid.mutate_far();
     if new_grid == grid {
-      return grid.count_occupied() as i64;
+      return Ok(grid.count_occupied());
     }
     grid = new_grid;
   }
 }
 
-pub fn part1(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(parse_part_1(input))
+// Whether `grid` stops changing within a few hundred rounds, rather than repeating.
+// False once cancelled, so that a generator under a timeout gives up
+fn settles(grid: &Grid, mutate: fn(&Grid) -> Grid) -> bool {
+  let mut previous = grid.clone();
+  let mut grid = mutate(grid);
+  for _ in 0..500 {
+    if cancel::check().is_err() {
+      return false;
+    }
+    let next = mutate(&grid);
+    if next == grid {
+      return true;
+    }
+    if next == previous {
+      return false;
+    }
+    previous = grid;
+    grid = next;
+  }
+  false
 }
 
-pub fn part2(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(parse_part_2(input))
+pub struct Day11;
+
+impl Day for Day11 {
+  type Model = Grid;
+  const SOURCE: &'static str = include_str!("day11.rs");
+
+  fn day(&self) -> u32 {
+    11
+  }
+
+  fn title(&self) -> &'static str {
+    "Seating System"
+  }
+
+  fn parse(&self, input: &Input) -> Result<Self::Model, AocError> {
+    Grid::new(input)
+  }
+
+  fn part1(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part1(model)?.into())
+  }
+
+  fn part2(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part2(model)?.into())
+  }
+
+  // `size` rows (1 to 100) of empty seats, each up to twice as wide as the grid is tall
+  // and at most 100, about a fifth of them floor. Some layouts flip back and forth
+  // forever under one set of rules or the other, so those are rolled again.
+  fn generate(&self, rng: &mut Rng, params: &Params) -> Option<String> {
+    let size = params.size.clamp(1, 100);
+    loop {
+      cancel::check().ok()?;
+      let cols = rng.range(1, 2 * size as i64).min(100);
+      let rows: Vec<String> = (0..size)
+        .map(|_| {
+          (0..cols)
+            .map(|_| if rng.one_in(5) { '.' } else { 'L' })
+            .collect()
+        })
+        .collect();
+      let input = generate::lines(&rows);
+      let grid = parse(&input).ok()?;
+      if settles(&grid, Grid::mutate) && settles(&grid, Grid::mutate_far) {
+        return Some(input);
+      }
+    }
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
 
-  static INPUT_EXAMPLE_1: &str = r"L.LL.LL.LL
-LLLLLLL.LL
-L.L.L..L..
-LLLL.LL.LL
-L.LL.LL.LL
-L.LLLLL.LL
-..L.L.....
-LLLLLLLLLL
-L.LLLLLL.L
-L.LLLLL.LL";
-
   static SIMPLE_EXAMPLE: &str = r".#
 #.";
 
   #[test]
   fn test_adjacent() {
-    let grid = Grid::new(SIMPLE_EXAMPLE);
+    let grid = Grid::new(&Input::new(SIMPLE_EXAMPLE)).unwrap();
     assert_eq!(2, grid.count_occupied_adjacents(0, 0));
     assert_eq!(1, grid.count_occupied_adjacents(0, 1));
   }
 
   #[test]
-  fn test_part1() {
-    assert_eq!(37, parse_part_1(INPUT_EXAMPLE_1));
+  fn test_grid() {
+    let grid = parse(SIMPLE_EXAMPLE).unwrap();
+    assert_eq!((2, 2), (grid.rows(), grid.cols()));
+    assert_eq!(Some(&Position::Occupied), grid.get(0, 1));
+    assert_eq!(".#\n#.\n", grid.to_string());
+    assert_eq!("##\n##\n", parse("LL\nLL").unwrap().mutate().to_string());
   }
+
   #[test]
-  fn test_part2() {
-    assert_eq!(26, parse_part_2(INPUT_EXAMPLE_1));
+  fn test_rectangular() {
+    let grid = parse("L.L\nLLL").unwrap();
+    assert_eq!((2, 3), (grid.rows(), grid.cols()));
+    assert_eq!(".\nL\n", parse(".\nL").unwrap().to_string());
+    assert_eq!(None, grid.get(0, 3));
+    assert_eq!(Ok(4), solve_part1(&grid));
+    assert_eq!(Ok(3), solve_part1(&parse("L\n.\nL\nL").unwrap()));
+  }
+
+  #[test]
+  fn test_parse_errors() {
+    match Grid::new(&Input::new("L.L\nL.\nLLL")) {
+      Err(AocError::Parse { line, column, .. }) => assert_eq!((2, 3), (line, column)),
+      _ => panic!("expected a parse error"),
+    }
+    match Grid::new(&Input::new("L.L\nL?L")) {
+      Err(AocError::Parse { line, column, .. }) => assert_eq!((2, 2), (line, column)),
+      _ => panic!("expected a parse error"),
+    }
   }
 }
@@ -1,7 +1,12 @@
-use std::error::Error;
+use crate::error::parse_number;
+use crate::generate::{self, Params};
+use crate::input::Input;
+use crate::rng::Rng;
+use crate::{Answer, AocError, Day};
 
-#[derive(Debug, PartialEq)]
-enum Action {
+// One navigation instruction; turns are always 90, 180 or 270 degrees
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
   North(i64),
   East(i64),
   South(i64),
@@ -14,37 +19,39 @@ enum Action {
 // (north, east, facing)
 type Tuple = (i64, i64, i64);
 
-fn parse_action(input: &str) -> Action {
-  let (action, value) = input.split_at(1);
-  let value: i64 = value.parse().unwrap();
+fn parse_action(input: &str) -> Result<Action, AocError> {
+  let split_index = input.chars().next().map_or(0, |c| c.len_utf8());
+  let (action, value) = input.split_at(split_index);
+  let value: i64 = parse_number(input, value, "a distance or angle")?;
   match action {
-    "N" => Action::North(value),
-    "E" => Action::East(value),
-    "S" => Action::South(value),
-    "W" => Action::West(value),
-    "L" => Action::Left(value),
-    "R" => Action::Right(value),
-    "F" => Action::Forward(value),
-    _ => panic!("Unexpected input"),
+    "N" => Ok(Action::North(value)),
+    "E" => Ok(Action::East(value)),
+    "S" => Ok(Action::South(value)),
+    "W" => Ok(Action::West(value)),
+    "L" | "R" if ![90, 180, 270].contains(&value) => Err(AocError::parse(
+      input,
+      &input[split_index..],
+      "expected a turn of 90, 180 or 270 degrees",
+    )),
+    "L" => Ok(Action::Left(value)),
+    "R" => Ok(Action::Right(value)),
+    "F" => Ok(Action::Forward(value)),
+    other => Err(AocError::parse(
+      input,
+      other,
+      "expected one of N, E, S, W, L, R or F",
+    )),
   }
 }
 
-fn process_direction(tuple: &mut Tuple, action: Action) {
+fn process_direction(tuple: &mut Tuple, action: Action) -> Result<(), AocError> {
   match action {
-    Action::North(val) => {
-      tuple.0 += val;
-    }
-    Action::South(val) => {
-      tuple.0 -= val;
-    }
-    Action::East(val) => {
-      tuple.1 += val;
-    }
-    Action::West(val) => {
-      tuple.1 -= val;
-    }
+    Action::North(val) => tuple.0 = add(tuple.0, val)?,
+    Action::South(val) => tuple.0 = add(tuple.0, negate(val)?)?,
+    Action::East(val) => tuple.1 = add(tuple.1, val)?,
+    Action::West(val) => tuple.1 = add(tuple.1, negate(val)?)?,
     Action::Left(val) => {
-      tuple.2 = tuple.2 - val;
+      tuple.2 -= val;
       if tuple.2 < 0 {
         tuple.2 += 360;
       }
@@ -53,108 +60,218 @@ fn process_direction(tuple: &mut Tuple, action: Action) {
       tuple.2 = (tuple.2 + val) % 360;
     }
     Action::Forward(val) => match tuple.2 {
-      0 => process_direction(tuple, Action::North(val)),
-      90 => process_direction(tuple, Action::East(val)),
-      180 => process_direction(tuple, Action::South(val)),
-      270 => process_direction(tuple, Action::West(val)),
-      _ => panic!(format!("Unexpected direction {}", tuple.2)),
+      0 => process_direction(tuple, Action::North(val))?,
+      90 => process_direction(tuple, Action::East(val))?,
+      180 => process_direction(tuple, Action::South(val))?,
+      270 => process_direction(tuple, Action::West(val))?,
+      _ => unreachable!("turns are validated by parse_action"),
     },
   }
+  Ok(())
 }
 
-fn process_correct_directions(ship: &mut Tuple, waypoint: &mut Tuple, action: Action) {
+fn process_correct_directions(
+  ship: &mut Tuple,
+  waypoint: &mut Tuple,
+  action: Action,
+) -> Result<(), AocError> {
   match action {
-    Action::North(val) => {
-      waypoint.0 += val;
-    }
-    Action::South(val) => {
-      waypoint.0 -= val;
-    }
-    Action::East(val) => {
-      waypoint.1 += val;
-    }
-    Action::West(val) => {
-      waypoint.1 -= val;
-    }
+    Action::North(val) => waypoint.0 = add(waypoint.0, val)?,
+    Action::South(val) => waypoint.0 = add(waypoint.0, negate(val)?)?,
+    Action::East(val) => waypoint.1 = add(waypoint.1, val)?,
+    Action::West(val) => waypoint.1 = add(waypoint.1, negate(val)?)?,
     Action::Left(val) => match val {
       90 => {
         let temp = waypoint.0;
         waypoint.0 = waypoint.1;
-        waypoint.1 = -temp;
+        waypoint.1 = negate(temp)?;
       }
       180 => {
-        waypoint.0 = -waypoint.0;
-        waypoint.1 = -waypoint.1;
+        waypoint.0 = negate(waypoint.0)?;
+        waypoint.1 = negate(waypoint.1)?;
       }
       270 => {
         let temp = waypoint.0;
-        waypoint.0 = -waypoint.1;
+        waypoint.0 = negate(waypoint.1)?;
         waypoint.1 = temp;
       }
-      _ => todo!(),
+      _ => unreachable!("turns are validated by parse_action"),
     },
-    Action::Right(val) => process_correct_directions(ship, waypoint, Action::Left(360 - val)),
+    Action::Right(val) => process_correct_directions(ship, waypoint, Action::Left(360 - val))?,
     Action::Forward(val) => {
-      ship.0 += val * waypoint.0;
-      ship.1 += val * waypoint.1;
+      ship.0 = add(ship.0, multiply(val, waypoint.0)?)?;
+      ship.1 = add(ship.1, multiply(val, waypoint.1)?)?;
     }
   }
+  Ok(())
 }
 
-fn parse_part_1(input: &str) -> i64 {
-  let actions: Vec<Action> = input.lines().map(|x| parse_action(x)).collect();
-  let mut tuple: Tuple = (0, 0, 90);
-  for action in actions {
-    process_direction(&mut tuple, action);
-  }
+// Arithmetic on positions, failing rather than wrapping if the ship sails out of range
+fn overflow() -> AocError {
+  AocError::NoSolution(String::from("the ship sailed too far to track"))
+}
 
-  tuple.0.abs() + tuple.1.abs()
+fn add(a: i64, b: i64) -> Result<i64, AocError> {
+  a.checked_add(b).ok_or_else(overflow)
 }
 
-pub fn part1(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(parse_part_1(input))
+fn multiply(a: i64, b: i64) -> Result<i64, AocError> {
+  a.checked_mul(b).ok_or_else(overflow)
+}
+
+fn negate(a: i64) -> Result<i64, AocError> {
+  a.checked_neg().ok_or_else(overflow)
+}
+
+// The Manhattan distance from the start
+fn distance(position: &Tuple) -> Result<i64, AocError> {
+  let north = position.0.checked_abs().ok_or_else(overflow)?;
+  add(north, position.1.checked_abs().ok_or_else(overflow)?)
+}
+
+pub fn parse(input: &str) -> Result<Vec<Action>, AocError> {
+  Input::new(input).parse_lines(parse_action)
+}
+
+// The Manhattan distance travelled when the actions move the ship
+pub fn solve_part1(actions: &[Action]) -> Result<i64, AocError> {
+  let mut tuple: Tuple = (0, 0, 90);
+  for &action in actions {
+    process_direction(&mut tuple, action)?;
+  }
+
+  distance(&tuple)
 }
 
-pub fn parse_part_2(input: &str) -> i64 {
-  let actions: Vec<Action> = input.lines().map(|x| parse_action(x)).collect();
+// The Manhattan distance travelled when the actions move the waypoint
+pub fn solve_part2(actions: &[Action]) -> Result<i64, AocError> {
   let mut ship: Tuple = (0, 0, 90);
   let mut waypoint: Tuple = (1, 10, 0);
-  for action in actions {
-    process_correct_directions(&mut ship, &mut waypoint, action);
+  for &action in actions {
+    process_correct_directions(&mut ship, &mut waypoint, action)?;
   }
 
-  ship.0.abs() + ship.1.abs()
+  distance(&ship)
 }
 
-pub fn part2(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(parse_part_2(input))
+pub struct Day12;
+
+impl Day for Day12 {
+  type Model = Vec<Action>;
+  const SOURCE: &'static str = include_str!("day12.rs");
+
+  fn day(&self) -> u32 {
+    12
+  }
+
+  fn title(&self) -> &'static str {
+    "Rain Risk"
+  }
+
+  fn parse(&self, input: &Input) -> Result<Self::Model, AocError> {
+    input.parse_lines(parse_action)
+  }
+
+  fn part1(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part1(model)?.into())
+  }
+
+  fn part2(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part2(model)?.into())
+  }
+
+  // `size` instructions, mostly moves of up to 100
+  fn generate(&self, rng: &mut Rng, params: &Params) -> Option<String> {
+    let actions: Vec<String> = (0..params.size.max(1))
+      .map(
+        |_| match *rng.pick(&['N', 'E', 'S', 'W', 'L', 'R', 'F', 'F']) {
+          turn @ ('L' | 'R') => format!("{}{}", turn, rng.range(1, 3) * 90),
+          action => format!("{}{}", action, rng.range(1, 100)),
+        },
+      )
+      .collect();
+    Some(generate::lines(&actions))
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use std::fs;
+  use crate::property::{self, Ints, OneOf, Vecs};
 
-  static INPUT_EXAMPLE_1: &str = r"F10
-N3
-F7
-R90
-F11";
+  fn action(kind: char, value: i64) -> Action {
+    match kind {
+      'N' => Action::North(value),
+      'E' => Action::East(value),
+      'S' => Action::South(value),
+      'W' => Action::West(value),
+      'L' => Action::Left(value % 3 * 90 + 90),
+      'R' => Action::Right(value % 3 * 90 + 90),
+      _ => Action::Forward(value),
+    }
+  }
 
+  // Turning every N into E, every E into S and so on turns the whole route a quarter
+  // turn clockwise, as long as the ship and waypoint start off turned as well, since F
+  // follows them. Either way the distance travelled doesn't change.
   #[test]
-  fn test_parse_action() {
-    assert_eq!(Action::East(50), parse_action("E50"));
+  fn test_rotation() {
+    let actions = Vecs {
+      item: (
+        OneOf(vec!['F', 'N', 'E', 'S', 'W', 'L', 'R']),
+        Ints { low: 1, high: 100 },
+      ),
+      min: 0,
+      max: 20,
+    };
+    property::check(&actions, |actions| {
+      let actions: Vec<Action> = actions
+        .iter()
+        .map(|&(kind, value)| action(kind, value))
+        .collect();
+      let mut rotated = vec![Action::Right(90)];
+      rotated.extend(actions.iter().map(|&action| match action {
+        Action::North(value) => Action::East(value),
+        Action::East(value) => Action::South(value),
+        Action::South(value) => Action::West(value),
+        Action::West(value) => Action::North(value),
+        other => other,
+      }));
+      for (part, solve) in [(1, solve_part1 as fn(&[Action]) -> _), (2, solve_part2)] {
+        let (before, after) = (solve(&actions), solve(&rotated));
+        if before != after {
+          return Err(format!(
+            "part {} went {:?}, but {:?} once rotated",
+            part, before, after
+          ));
+        }
+      }
+      Ok(())
+    });
   }
 
   #[test]
-  fn test_part1() {
-    assert_eq!(25, parse_part_1(INPUT_EXAMPLE_1));
+  fn test_parse_action() {
+    assert_eq!(Action::East(50), parse_action("E50").unwrap());
+    for (input, column) in [("", 1), ("X50", 1), ("E5x", 2), ("L45", 2)] {
+      match parse_action(input) {
+        Err(AocError::Parse { column: c, .. }) => assert_eq!(column, c, "{}", input),
+        _ => panic!("expected a parse error for {:?}", input),
+      }
+    }
   }
 
   #[test]
-  fn test_part2() {
-    let input = fs::read_to_string("input/day12-test.txt").unwrap();
-    assert_eq!(286, parse_part_2(INPUT_EXAMPLE_1));
-    assert_eq!(30761, parse_part_2(&input));
+  fn test_overflow() {
+    let actions = parse("N9223372036854775807\nN1").unwrap();
+    assert!(matches!(
+      solve_part1(&actions),
+      Err(AocError::NoSolution(_))
+    ));
+    let actions = parse("F9223372036854775807").unwrap();
+    assert!(matches!(
+      solve_part2(&actions),
+      Err(AocError::NoSolution(_))
+    ));
   }
 }
@@ -1,53 +1,93 @@
-use std::error::Error;
-
-fn parse_input(input: &str) -> (i64, Vec<i64>) {
-  let lines: Vec<&str> = input.lines().collect();
-  let target: i64 = lines[0].parse().unwrap();
-  let buses: Vec<i64> = lines[1]
-    .split(',')
-    .filter(|x| x != &"x")
-    .map(|x| x.parse().unwrap())
-    .collect();
-
-  return (target, buses);
+use crate::cancel;
+use crate::error::parse_number;
+use crate::generate::Params;
+use crate::input::Input;
+use crate::rng::Rng;
+use crate::{Alternative, Answer, AocError, Day};
+
+fn parse_bus(line: &str, bus: &str) -> Result<i64, AocError> {
+  match parse_number(line, bus, "a bus ID or x")? {
+    id if id > 0 => Ok(id),
+    _ => Err(AocError::parse(line, bus, "bus IDs must be positive")),
+  }
+}
+
+// The line of bus IDs, which is always the second line of input
+fn bus_line(input: &Input) -> Result<&str, AocError> {
+  input
+    .lines()
+    .nth(1)
+    .ok_or_else(|| AocError::parse("", "", "expected a line of bus IDs").at_line(2))
 }
 
-fn parse_part_1(input: &str) -> i64 {
-  let (target, buses) = parse_input(input);
+// The earliest departure, and each bus in service with its position in the list
+#[derive(Debug, PartialEq)]
+pub struct Notes {
+  pub timestamp: i64,
+  // (offset, bus ID)
+  pub buses: Vec<(i64, i64)>,
+}
 
+fn parse_input(input: &Input) -> Result<Notes, AocError> {
+  let first = input.lines().next().unwrap_or("");
+  let timestamp: i64 = parse_number(first, first, "a timestamp").map_err(|err| err.at_line(1))?;
+  Ok(Notes {
+    timestamp,
+    buses: parse_offset_buses(input)?,
+  })
+}
+
+pub fn parse(input: &str) -> Result<Notes, AocError> {
+  parse_input(&Input::new(input))
+}
+
+// The ID of the first bus to leave after the timestamp, times the wait for it
+pub fn solve_part1(notes: &Notes) -> Result<i64, AocError> {
+  let target = notes.timestamp;
   let mut best: Option<i64> = None;
   let mut answer = 0;
-  for bus in buses {
-    let diff = (bus * (target / bus + 1)) - target;
-    if best == None || diff < best.unwrap() {
+  let overflow = || AocError::NoSolution(String::from("the next departure overflowed"));
+  for &(_, bus) in &notes.buses {
+    let departs = (target / bus)
+      .checked_add(1)
+      .and_then(|trips| trips.checked_mul(bus))
+      .ok_or_else(overflow)?;
+    let diff = departs - target;
+    if best.is_none() || diff < best.unwrap() {
       best = Some(diff);
-      answer = bus * diff;
+      answer = bus.checked_mul(diff).ok_or_else(overflow)?;
     }
   }
-  answer
-}
-
-pub fn part1(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(parse_part_1(input))
+  match best {
+    Some(_) => Ok(answer),
+    None => Err(AocError::NoSolution(String::from("there are no buses"))),
+  }
 }
 
-fn parse_offset_buses(input: &str) -> Vec<(i64, i64)> {
-  let line: Vec<&str> = input.lines().nth(1).unwrap().split(',').collect();
+fn parse_offset_buses(input: &Input) -> Result<Vec<(i64, i64)>, AocError> {
+  let text = bus_line(input)?;
+  let line: Vec<&str> = text.split(',').collect();
   let mut buses: Vec<(i64, i64)> = Vec::new();
   for (i, &bus) in line.iter().enumerate() {
     if bus == "x" {
       continue;
     }
-    buses.push((i as i64, bus.parse().unwrap()));
+    buses.push((
+      i as i64,
+      parse_bus(text, bus).map_err(|err| err.at_line(2))?,
+    ));
   }
-  buses
+  Ok(buses)
 }
 
-// Not used in the solution - takes far, far too long
-#[allow(dead_code)]
-fn part_2_brute_force(input: &str) -> i64 {
-  let buses = parse_offset_buses(input);
+// Far too slow for real input, but `diff-test` checks it against the real solution on
+// small ones
+fn part_2_brute_force(notes: &Notes) -> Result<i64, AocError> {
+  let buses = &notes.buses;
   for i in 0..i64::MAX {
+    if i % 0x10000 == 0 {
+      cancel::check()?;
+    }
     let mut found = true;
     for (x, bus) in buses.iter() {
       if (i + x) % bus != 0 {
@@ -56,64 +96,147 @@ fn part_2_brute_force(input: &str) -> i64 {
       }
     }
     if found {
-      return i;
+      return Ok(i);
     }
   }
-  panic!("No results found");
+  Err(AocError::NoSolution(String::from(
+    "no timestamp below i64::MAX",
+  )))
 }
 
-fn part_2_reduce_search_space(input: &str) -> i64 {
-  let buses = parse_offset_buses(input);
-  let mut current = buses[0].1;
-  let mut next_index = 1;
+fn part_2_reduce_search_space(notes: &Notes) -> Result<i64, AocError> {
+  let buses = &notes.buses;
+  let overflow = || AocError::NoSolution(String::from("no timestamp below i64::MAX"));
+  if buses.is_empty() {
+    return Err(AocError::NoSolution(String::from("there are no buses")));
+  }
+  // Timestamp 0 counts, as it does for the brute force
+  let mut current = 0i64;
+  let mut next_index = 0;
   loop {
+    cancel::check()?;
     for (offset, bus) in buses[next_index..].iter() {
-      if (current + offset) % bus != 0 {
+      if current.checked_add(*offset).ok_or_else(overflow)? % bus != 0 {
         break;
       }
       // Increment our next_index "pointer" to the next_index
       next_index += 1;
       if next_index == buses.len() {
         // We found a match!
-        return current;
+        return Ok(current);
       }
     }
     // Given current equals a number that has been previously found to be divisible by buses[0..next_index]
     // it follows that the next number to check ought to be the current number plus the
     // product of buses[0..next_index] (provided that they are all primes, which they are)
-    current += buses[0..next_index].iter().fold(1, |a, (_, bus)| a * bus);
+    let step = buses[0..next_index]
+      .iter()
+      .try_fold(1i64, |a, (_, bus)| a.checked_mul(*bus))
+      .ok_or_else(overflow)?;
+    current = current.checked_add(step).ok_or_else(overflow)?;
   }
 }
 
-pub fn part2(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(part_2_reduce_search_space(input))
+// The first timestamp where every bus leaves at its offset from it
+pub fn solve_part2(notes: &Notes) -> Result<i64, AocError> {
+  part_2_reduce_search_space(notes)
+}
+
+pub struct Day13;
+
+impl Day for Day13 {
+  type Model = Notes;
+  const SOURCE: &'static str = include_str!("day13.rs");
+
+  fn day(&self) -> u32 {
+    13
+  }
+
+  fn title(&self) -> &'static str {
+    "Shuttle Search"
+  }
+
+  fn parse(&self, input: &Input) -> Result<Self::Model, AocError> {
+    parse_input(input)
+  }
+
+  fn part1(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part1(model)?.into())
+  }
+
+  fn part2(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part2(model)?.into())
+  }
+
+  fn alternatives(&self) -> Vec<Alternative<Self::Model>> {
+    vec![Alternative {
+      part: 2,
+      name: "brute force",
+      solve: |notes| Ok(part_2_brute_force(notes)?.into()),
+    }]
+  }
+
+  // Up to `size` buses with distinct prime IDs, so there's always an answer, kept small
+  // enough for the brute force
+  fn generate(&self, rng: &mut Rng, params: &Params) -> Option<String> {
+    let mut primes = vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47];
+    rng.shuffle(&mut primes);
+    let mut product = 1;
+    let mut slots = Vec::new();
+    for &bus in primes.iter().take(params.size.max(1)) {
+      if product * bus > 1_000_000 {
+        break;
+      }
+      product *= bus;
+      // Like the real notes, the list starts with a bus
+      if !slots.is_empty() {
+        slots.extend((0..rng.below(3)).map(|_| String::from("x")));
+      }
+      slots.push(bus.to_string());
+    }
+    Some(format!(
+      "{}\n{}\n",
+      rng.range(1, 1_000_000),
+      slots.join(",")
+    ))
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::examples::Example;
+  use std::time::Duration;
 
-  static INPUT_EXAMPLE_1: &str = r"939
-7,13,x,x,59,x,31,19";
-
-  static PART_TWO_EXAMPLES: &'static [(i64, &str)] = &[
-    (3417, "\n17,x,13,19"),
-    (754018, "\n67,7,59,61"),
-    (779210, "\n67,x,7,59,61"),
-    (1261476, "\n67,7,x,59,61"),
-    (1202161486, "\n1789,37,47,1889"),
-  ];
+  #[test]
+  fn test_parse() {
+    let notes = parse(&Example::load(13, "1").unwrap().input).unwrap();
+    assert_eq!((939, (4, 59)), (notes.timestamp, notes.buses[2]));
+  }
 
   #[test]
-  fn test_part1() {
-    assert_eq!(295, parse_part_1(INPUT_EXAMPLE_1));
+  fn test_brute_force_is_cancelled() {
+    let limit = Duration::from_millis(20);
+    let notes = parse("939\n1789,37,47,1889").unwrap();
+    let result = cancel::with_timeout(limit, move || part_2_brute_force(&notes));
+    assert_eq!(Err(AocError::Timeout(limit)), result);
   }
 
   #[test]
-  fn test_part2() {
-    assert_eq!(1068781, part_2_reduce_search_space(INPUT_EXAMPLE_1));
-    for &(expected, input) in PART_TWO_EXAMPLES {
-      assert_eq!(expected, part_2_reduce_search_space(input));
+  fn test_parse_errors() {
+    match parse("939\n7,13,x,0") {
+      Err(AocError::Parse { line, column, .. }) => assert_eq!((2, 8), (line, column)),
+      _ => panic!("expected a parse error"),
+    }
+    match parse("939") {
+      Err(AocError::Parse { line, .. }) => assert_eq!(2, line),
+      _ => panic!("expected a parse error"),
     }
   }
+
+  #[test]
+  fn test_part1_overflow() {
+    let notes = parse("9223372036854775807\n2").unwrap();
+    assert!(matches!(solve_part1(&notes), Err(AocError::NoSolution(_))));
+  }
 }
@@ -1,34 +1,58 @@
+use crate::error::parse_number;
+use crate::generate::{self, Params};
+use crate::input::Input;
+use crate::rng::Rng;
+use crate::{Answer, AocError, Day};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::HashMap;
-use std::error::Error;
 
 lazy_static! {
   static ref MEMSET_REGEX: Regex = Regex::new(r"^mem\[(\d+)\]\s=\s(\d+)$").unwrap();
 }
 
-#[derive(Debug)]
-enum Operation<'a> {
-  Mask(&'a str),
+// One line of the initialization program: a new mask, or a write of (address, value)
+#[derive(Debug, PartialEq)]
+pub enum Operation {
+  Mask(String),
   MemSet((i64, i64)),
 }
 
-fn parse_operation<'a>(input: &'a str) -> Operation<'a> {
+fn parse_operation(input: &str) -> Result<Operation, AocError> {
   // ^mem\[(\d+)\]\s=\s(\d+)$
-  if input.starts_with("mask") {
-    let mask: &str = input.split(' ').nth(2).unwrap();
-    return Operation::Mask(mask);
+  if let Some(mask) = input.strip_prefix("mask = ") {
+    if let Some(index) = mask.find(|c| c != 'X' && c != '0' && c != '1') {
+      return Err(AocError::parse(input, &mask[index..], "expected X, 0 or 1"));
+    }
+    if mask.len() != 36 {
+      return Err(AocError::parse(input, mask, "expected a mask of 36 bits"));
+    }
+    return Ok(Operation::Mask(String::from(mask)));
+  }
+  let captures = MEMSET_REGEX.captures(input).ok_or_else(|| {
+    AocError::parse(
+      input,
+      input,
+      "expected \"mask = ...\" or \"mem[address] = value\"",
+    )
+  })?;
+  let address = &input[captures.get(1).unwrap().range()];
+  let value = &input[captures.get(2).unwrap().range()];
+  Ok(Operation::MemSet((
+    parse_36_bits(input, address, "an address")?,
+    parse_36_bits(input, value, "a value")?,
+  )))
+}
+
+// Addresses and values are 36-bit unsigned integers, which also keeps the sum of every
+// value in memory well within an i64
+fn parse_36_bits(input: &str, token: &str, expected: &str) -> Result<i64, AocError> {
+  let number: i64 = parse_number(input, token, expected)?;
+  if number >= 1 << 36 {
+    let message = format!("expected {} of at most 36 bits, found {}", expected, number);
+    return Err(AocError::parse(input, token, message));
   }
-  let captures = MEMSET_REGEX.captures(input).unwrap();
-  let address: i64 = captures
-    .get(1)
-    .map(|x| x.as_str().parse().unwrap())
-    .unwrap();
-  let value: i64 = captures
-    .get(2)
-    .map(|x| x.as_str().parse().unwrap())
-    .unwrap();
-  Operation::MemSet((address, value))
+  Ok(number)
 }
 
 fn apply_mask(mask: &str, value: i64) -> i64 {
@@ -36,43 +60,112 @@ fn apply_mask(mask: &str, value: i64) -> i64 {
   let mut output = value;
   for (i, bit) in mask.chars().enumerate() {
     match bit {
-      '1' => output = output | (1 << len - i),
-      '0' => output = output & !(1 << len - i),
+      '1' => output |= 1 << (len - i),
+      '0' => output &= !(1 << (len - i)),
       _ => continue,
     }
   }
   output
 }
 
-fn run_part_1(input: &str) -> i64 {
+pub fn parse(input: &str) -> Result<Vec<Operation>, AocError> {
+  Input::new(input).parse_lines(parse_operation)
+}
+
+// The sum of memory once every write has been masked
+pub fn solve_part1(program: &[Operation]) -> Result<i64, AocError> {
   let mut memory: HashMap<i64, i64> = HashMap::new();
   let mut mask: &str = "";
-  for line in input.lines() {
-    let operation = parse_operation(line);
+  for operation in program {
     if let Operation::Mask(val) = operation {
       mask = val;
-    } else if let Operation::MemSet((address, value)) = operation {
+    } else if let &Operation::MemSet((address, value)) = operation {
       memory.insert(address, apply_mask(mask, value));
     }
   }
-  memory.into_iter().fold(0, |a, (_, v)| v + a)
+  Ok(memory.into_iter().fold(0, |a, (_, v)| v + a))
 }
 
-pub fn part1(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(run_part_1(input))
+pub struct Day14;
+
+impl Day for Day14 {
+  type Model = Vec<Operation>;
+  const SOURCE: &'static str = include_str!("day14.rs");
+
+  fn day(&self) -> u32 {
+    14
+  }
+
+  fn title(&self) -> &'static str {
+    "Docking Data"
+  }
+
+  fn parse(&self, input: &Input) -> Result<Self::Model, AocError> {
+    input.parse_lines(parse_operation)
+  }
+
+  fn parts(&self) -> &'static [u32] {
+    &[1]
+  }
+
+  fn part1(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part1(model)?.into())
+  }
+
+  // `size` writes of 36-bit values, with a new mask every one to five of them. Like the
+  // real masks, each has at most nine Xs.
+  fn generate(&self, rng: &mut Rng, params: &Params) -> Option<String> {
+    let mut lines = Vec::new();
+    let mut writes = 0;
+    while writes < params.size.max(1) {
+      let floating = rng.range(0, 9);
+      let mut mask: Vec<char> = (0..36)
+        .map(|i| {
+          if i < floating {
+            'X'
+          } else {
+            *rng.pick(&['0', '1'])
+          }
+        })
+        .collect();
+      rng.shuffle(&mut mask);
+      lines.push(format!("mask = {}", mask.into_iter().collect::<String>()));
+      for _ in 0..rng.range(1, 5) {
+        let value = rng.below(1 << 36);
+        lines.push(format!("mem[{}] = {}", rng.below(65536), value));
+        writes += 1;
+      }
+    }
+    Some(generate::lines(&lines))
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::examples::Example;
 
-  static INPUT_EXAMPLE_1: &str = r"mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
-mem[8] = 11
-mem[7] = 101
-mem[8] = 0";
+  #[test]
+  fn test_parse() {
+    let program = parse(&Example::load(14, "1").unwrap().input).unwrap();
+    assert_eq!(Operation::MemSet((7, 101)), program[2]);
+  }
 
   #[test]
-  fn test_part1() {
-    assert_eq!(165, run_part_1(INPUT_EXAMPLE_1));
+  fn test_parse_operation() {
+    let inputs = vec![
+      ("mask = XX1", 8),
+      ("mask = XX2XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX", 10),
+      ("mem[8] 11", 1),
+      ("mem[8] = 99999999999999999999", 10),
+      ("mem[8] = 68719476736", 10),
+      ("mem[68719476736] = 1", 5),
+    ];
+    for (input, column) in inputs {
+      match parse_operation(input) {
+        Err(AocError::Parse { column: c, .. }) => assert_eq!(column, c, "{}", input),
+        _ => panic!("expected a parse error for {}", input),
+      }
+    }
   }
 }
@@ -1,17 +1,60 @@
-use std::error::Error;
+use crate::error::parse_number;
+use crate::generate::{self, Params};
+use crate::input::Input;
+use crate::rng::Rng;
+use crate::{Answer, AocError, Day};
 
-struct Input {
-  low: i64,
-  high: i64,
-  c: char,
-  password: String,
+// A password, and the two positions its policy gives for `letter`
+#[derive(Debug, PartialEq)]
+pub struct Policy {
+  pub low: i64,
+  pub high: i64,
+  pub letter: char,
+  pub password: String,
 }
 
-pub fn part1(input: &str) -> Result<i64, Box<dyn Error>> {
-  let inputs: Vec<Input> = input.split("\n").map(|input| parse_input(input)).collect();
-  let mut matches = 0;
-  for i in inputs {
-    let count = i.password.matches(i.c).collect::<Vec<&str>>().len() as i64;
+fn parse_input(input: &str) -> Result<Policy, AocError> {
+  let parts: Vec<&str> = input.split(' ').collect();
+  if parts.len() != 3 {
+    return Err(AocError::parse(
+      input,
+      input,
+      "expected a policy like \"1-3 a: abcde\"",
+    ));
+  }
+  let (low, high) = match parts[0].find('-') {
+    Some(index) => (&parts[0][..index], &parts[0][index + 1..]),
+    None => {
+      return Err(AocError::parse(
+        input,
+        parts[0],
+        "expected a range like \"1-3\"",
+      ))
+    }
+  };
+  let c: char = match parts[1].chars().next() {
+    Some(c) if c != ':' => c,
+    _ => return Err(AocError::parse(input, parts[1], "expected a letter")),
+  };
+  let password = String::from(parts[2]);
+
+  Ok(Policy {
+    low: parse_number(input, low, "a position")?,
+    high: parse_number(input, high, "a position")?,
+    letter: c,
+    password,
+  })
+}
+
+// One policy and password per line
+pub fn parse(input: &str) -> Result<Vec<Policy>, AocError> {
+  Input::new(input).parse_lines(parse_input)
+}
+
+pub fn solve_part1(policies: &[Policy]) -> Result<usize, AocError> {
+  let mut matches: usize = 0;
+  for i in policies {
+    let count = i.password.matches(i.letter).count() as i64;
     if count >= i.low && count <= i.high {
       matches += 1;
     }
@@ -19,14 +62,15 @@ pub fn part1(input: &str) -> Result<i64, Box<dyn Error>> {
   Ok(matches)
 }
 
-pub fn part2(input: &str) -> Result<i64, Box<dyn Error>> {
-  let inputs: Vec<Input> = input.split("\n").map(|input| parse_input(input)).collect();
-  let mut matches = 0;
-  for i in inputs {
-    let indices: Vec<_> = i.password.match_indices(i.c).collect();
+pub fn solve_part2(policies: &[Policy]) -> Result<usize, AocError> {
+  let mut matches: usize = 0;
+  for i in policies {
+    let indices: Vec<_> = i.password.match_indices(i.letter).collect();
     let mut inner_match = 0;
     for (loc, _) in indices {
-      if loc == (i.low - 1) as usize || loc == (i.high - 1) as usize {
+      // Positions count from 1, so no letter is at position 0 or below
+      let position = loc as i64 + 1;
+      if position == i.low || position == i.high {
         inner_match += 1;
       }
     }
@@ -37,19 +81,127 @@ pub fn part2(input: &str) -> Result<i64, Box<dyn Error>> {
   Ok(matches)
 }
 
-fn parse_input(input: &str) -> Input {
-  let parts: Vec<&str> = input.split(' ').collect();
-  let low_high: Vec<i64> = parts[0]
-    .split('-')
-    .map(|val| val.parse().unwrap())
-    .collect();
-  let c: char = parts[1].chars().nth(0).unwrap();
-  let password = String::from(parts[2]);
+pub struct Day2;
 
-  Input {
-    low: low_high[0],
-    high: low_high[1],
-    c,
-    password,
+impl Day for Day2 {
+  type Model = Vec<Policy>;
+  const SOURCE: &'static str = include_str!("day2.rs");
+
+  fn day(&self) -> u32 {
+    2
+  }
+
+  fn title(&self) -> &'static str {
+    "Password Philosophy"
+  }
+
+  fn parse(&self, input: &Input) -> Result<Self::Model, AocError> {
+    input.parse_lines(parse_input)
+  }
+
+  fn part1(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part1(model)?.into())
+  }
+
+  fn part2(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part2(model)?.into())
+  }
+
+  // `size` passwords, of which `invalid` percent break both policies by not having the
+  // letter at all, and the rest meet both
+  fn generate(&self, rng: &mut Rng, params: &Params) -> Option<String> {
+    let invalid = generate::invalid_count(params);
+    let mut lines: Vec<String> = (0..params.size.max(1))
+      .map(|i| {
+        let low = rng.range(1, 5) as usize;
+        let high = low + rng.range(1, 10) as usize;
+        let letter = (b'a' + rng.below(26) as u8) as char;
+        let others: Vec<char> = ('a'..='z').filter(|&c| c != letter).collect();
+        let mut password: Vec<char> = (0..high + rng.range(0, 5) as usize)
+          .map(|_| *rng.pick(&others))
+          .collect();
+        if i >= invalid {
+          // At `low` but not `high`, and between `low` and `high` times in all
+          password[low - 1] = letter;
+          let mut rest: Vec<usize> = (0..password.len())
+            .filter(|&index| index != low - 1 && index != high - 1)
+            .collect();
+          rng.shuffle(&mut rest);
+          for &index in rest
+            .iter()
+            .take(rng.range(low as i64, high as i64) as usize - 1)
+          {
+            password[index] = letter;
+          }
+        }
+        let password: String = password.into_iter().collect();
+        format!("{}-{} {}: {}", low, high, letter, password)
+      })
+      .collect();
+    rng.shuffle(&mut lines);
+    Some(generate::lines(&lines))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_generate() {
+    let params = Params {
+      size: 40,
+      invalid: 25.0,
+    };
+    let policies = parse(&Day2.generate(&mut Rng::new(1), &params).unwrap()).unwrap();
+    assert_eq!(40, policies.len());
+    assert_eq!(Ok(30), solve_part1(&policies));
+    assert_eq!(Ok(30), solve_part2(&policies));
+  }
+
+  #[test]
+  fn test_parse_error() {
+    let err = Input::new("1-3 a: abcde\n1-x b: cdefg")
+      .parse_lines(parse_input)
+      .err();
+    assert_eq!(
+      Some(AocError::Parse {
+        line: 2,
+        column: 3,
+        text: String::from("1-x b: cdefg"),
+        message: String::from("expected a position, found \"x\""),
+      }),
+      err
+    );
+  }
+
+  #[test]
+  fn test_line_endings() {
+    let input = Input::new("\u{feff}1-3 a: abcde\r\n1-3 b: cdefg\r\n2-9 c: ccccccccc\r\n");
+    let policies = Day2.parse(&input).unwrap();
+    assert_eq!(Answer::Int(2), Day2.part1(&policies).unwrap());
+    assert_eq!(Answer::Int(1), Day2.part2(&policies).unwrap());
+  }
+
+  #[test]
+  fn test_parse() {
+    let policies = parse("1-3 a: abcde\n2-9 c: ccccccccc").unwrap();
+    assert_eq!(
+      Policy {
+        low: 2,
+        high: 9,
+        letter: 'c',
+        password: String::from("ccccccccc"),
+      },
+      policies[1]
+    );
+    assert_eq!(Ok(2), solve_part1(&policies));
+  }
+
+  #[test]
+  fn test_extreme_positions() {
+    let policies = parse("1--9223372036854775808 a: abc\n0-9223372036854775807 b: abc").unwrap();
+    assert_eq!(Ok(1), solve_part1(&policies));
+    assert_eq!(Ok(1), solve_part2(&policies));
   }
 }
@@ -1,15 +1,22 @@
-use std::error::Error;
+use crate::generate::{self, Params};
+use crate::input::Input;
+use crate::rng::Rng;
+use crate::{Answer, AocError, Day};
 
 // x, y (right, down)
-struct Point(usize, usize);
+#[derive(Debug, PartialEq)]
+pub struct Point(pub usize, pub usize);
 
-struct Map {
-  trees: Vec<Point>,
-  columns: usize,
+// Where the trees are in one repetition of the map, which repeats to the right
+#[derive(Debug)]
+pub struct Map {
+  pub trees: Vec<Point>,
+  pub columns: usize,
 }
 
 impl Map {
-  fn count_trees(&self, slope: &Point) -> usize {
+  // The trees hit going from the top left by `slope` until the bottom
+  pub fn count_trees(&self, slope: &Point) -> usize {
     self
       .trees
       .iter()
@@ -20,67 +27,97 @@ impl Map {
   }
 }
 
-fn parse_input(input: &str) -> Map {
-  let mut trees = Vec::new();
-  let mut columns = 0;
-
-  for (down, line) in input.lines().enumerate() {
-    columns = 0;
-    for (right, c) in line.chars().enumerate() {
-      if c == '#' {
-        trees.push(Point(right, down));
-      }
-      columns += 1;
-    }
-  }
-  Map { columns, trees }
+fn parse_input(input: &Input) -> Result<Map, AocError> {
+  let grid = input.grid(".#")?;
+  let trees = grid
+    .iter()
+    .enumerate()
+    .flat_map(|(down, row)| {
+      row
+        .iter()
+        .enumerate()
+        .filter(|(_, &c)| c == '#')
+        .map(move |(right, _)| Point(right, down))
+    })
+    .collect();
+  Ok(Map {
+    columns: grid[0].len(),
+    trees,
+  })
+}
+
+pub fn parse(input: &str) -> Result<Map, AocError> {
+  parse_input(&Input::new(input))
 }
 
-pub fn part1(input: &str) -> Result<i64, Box<dyn Error>> {
-  let map = parse_input(input);
-  Ok(map.count_trees(&Point(3, 1)) as i64)
+pub fn solve_part1(map: &Map) -> Result<usize, AocError> {
+  Ok(map.count_trees(&Point(3, 1)))
 }
 
-pub fn part2(input: &str) -> Result<i64, Box<dyn Error>> {
-  let map = parse_input(input);
-  let points = vec![
+pub fn solve_part2(map: &Map) -> Result<usize, AocError> {
+  let points = [
     Point(1, 1),
     Point(3, 1),
     Point(5, 1),
     Point(7, 1),
     Point(1, 2),
   ];
-
   Ok(
     points
       .iter()
-      .fold(1, |acc, point| acc * map.count_trees(point)) as i64,
+      .fold(1, |acc, point| acc * map.count_trees(point)),
   )
 }
 
-#[cfg(test)]
-mod tests {
-  use super::*;
+pub struct Day3;
 
-  static INPUT: &str = r"..##.......
-#...#...#..
-.#....#..#.
-..#.#...#.#
-.#...##..#.
-..#.##.....
-.#.#.#....#
-.#........#
-#.##...#...
-#...##....#
-.#..#...#.#";
+impl Day for Day3 {
+  type Model = Map;
+  const SOURCE: &'static str = include_str!("day3.rs");
 
-  #[test]
-  fn tutorial_passes() {
-    assert_eq!(7, part1(INPUT).unwrap());
+  fn day(&self) -> u32 {
+    3
+  }
+
+  fn title(&self) -> &'static str {
+    "Toboggan Trajectory"
+  }
+
+  fn parse(&self, input: &Input) -> Result<Self::Model, AocError> {
+    parse_input(input)
   }
 
+  fn part1(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part1(model)?.into())
+  }
+
+  fn part2(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part2(model)?.into())
+  }
+
+  // `size` rows as wide as the real map's, about a quarter of them trees
+  fn generate(&self, rng: &mut Rng, params: &Params) -> Option<String> {
+    let rows: Vec<String> = (0..params.size.max(1))
+      .map(|_| {
+        (0..31)
+          .map(|_| if rng.one_in(4) { '#' } else { '.' })
+          .collect()
+      })
+      .collect();
+    Some(generate::lines(&rows))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::examples::Example;
+
   #[test]
-  fn part2_passes() {
-    assert_eq!(336, part2(INPUT).unwrap());
+  fn test_parse() {
+    let map = parse(&Example::load(3, "1").unwrap().input).unwrap();
+    assert_eq!(11, map.columns);
+    assert_eq!(Point(2, 0), map.trees[0]);
+    assert_eq!(Ok(7), solve_part1(&map));
   }
 }
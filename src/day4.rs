@@ -1,35 +1,37 @@
 extern crate regex;
+use crate::generate::{self, Params};
+use crate::input::Input;
+use crate::rng::Rng;
+use crate::{Answer, AocError, Day};
 use regex::Regex;
-use std::error::Error;
-
-struct Passport {
-  birth_year: Option<String>,
-  issue_year: Option<String>,
-  expiration_year: Option<String>,
-  height: Option<String>,
-  hair_color: Option<String>,
-  eye_color: Option<String>,
-  passport_id: Option<String>,
-  country_id: Option<String>,
+
+// Every field a card can have, as written on the card
+#[derive(Debug, Default, PartialEq)]
+pub struct Passport {
+  pub birth_year: Option<String>,
+  pub issue_year: Option<String>,
+  pub expiration_year: Option<String>,
+  pub height: Option<String>,
+  pub hair_color: Option<String>,
+  pub eye_color: Option<String>,
+  pub passport_id: Option<String>,
+  pub country_id: Option<String>,
 }
 
 impl Passport {
-  fn is_valid(&self) -> bool {
-    if self.birth_year.is_some()
+  // Every field but the country ID is present
+  pub fn is_valid(&self) -> bool {
+    self.birth_year.is_some()
       && self.issue_year.is_some()
       && self.expiration_year.is_some()
       && self.height.is_some()
       && self.hair_color.is_some()
       && self.eye_color.is_some()
       && self.passport_id.is_some()
-    {
-      true
-    } else {
-      false
-    }
   }
 
-  fn is_really_valid(&self) -> bool {
+  // Every field but the country ID is present and well-formed
+  pub fn is_really_valid(&self) -> bool {
     if !self.is_valid() {
       return false;
     }
@@ -73,7 +75,7 @@ impl Passport {
 }
 
 fn validate_eye(val: Option<String>) -> bool {
-  let valid = vec!["brn", "amb", "blu", "gry", "grn", "hzl", "oth"];
+  let valid = ["brn", "amb", "blu", "gry", "grn", "hzl", "oth"];
   if let Some(val) = val {
     return valid.contains(&val.as_str());
   }
@@ -100,8 +102,8 @@ fn validate_height(val: Option<String>) -> bool {
       .map_or(0, |m| m.as_str().parse().unwrap_or(0));
     let suffix = captures.get(2).map_or("", |m| m.as_str());
     let result: bool = match suffix {
-      "in" => height >= 59 && height <= 76,
-      "cm" => height >= 150 && height <= 193,
+      "in" => (59..=76).contains(&height),
+      "cm" => (150..=193).contains(&height),
       _ => false,
     };
     return result;
@@ -131,24 +133,21 @@ fn validate_pid(val: Option<String>) -> bool {
   false
 }
 
-fn parse_input(input: &str) -> Vec<Passport> {
-  input.split("\n\n").map(|c| parse_card(&c)).collect()
+fn parse_input(input: &Input) -> Result<Vec<Passport>, AocError> {
+  // Cards are separated by blank lines and may span several lines
+  input
+    .paragraphs()
+    .iter()
+    .map(|card| {
+      let mut passport = Passport::default();
+      card.parse_lines(|line| parse_card_line(&mut passport, line))?;
+      Ok(passport)
+    })
+    .collect()
 }
 
-fn parse_card(card: &str) -> Passport {
-  let items: Vec<&str> = card.split(|x| x == ' ' || x == '\n').collect();
-  let mut passport = Passport {
-    birth_year: None,
-    issue_year: None,
-    expiration_year: None,
-    height: None,
-    hair_color: None,
-    eye_color: None,
-    passport_id: None,
-    country_id: None,
-  };
-
-  for item in items {
+fn parse_card_line(passport: &mut Passport, line: &str) -> Result<(), AocError> {
+  for item in line.split(' ').filter(|item| !item.is_empty()) {
     let parsed = item.split(':').take(2).collect::<Vec<&str>>();
     if let [key, val] = parsed[..] {
       let val = val.to_string();
@@ -161,42 +160,180 @@ fn parse_card(card: &str) -> Passport {
         "ecl" => passport.eye_color = Some(val),
         "pid" => passport.passport_id = Some(val),
         "cid" => passport.country_id = Some(val),
-        other => panic!("Unexpected key: {}", other),
+        other => {
+          return Err(AocError::parse(
+            line,
+            other,
+            format!("unexpected key {:?}", other),
+          ))
+        }
       }
+    } else {
+      return Err(AocError::parse(line, item, "expected a key:value pair"));
     }
   }
 
-  passport
+  Ok(())
+}
+
+pub fn parse(input: &str) -> Result<Vec<Passport>, AocError> {
+  parse_input(&Input::new(input))
+}
+
+pub fn solve_part1(passports: &[Passport]) -> Result<usize, AocError> {
+  Ok(passports.iter().filter(|x| x.is_valid()).count())
 }
 
-pub fn part1(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(parse_input(input).iter().filter(|x| x.is_valid()).count() as i64)
+pub fn solve_part2(passports: &[Passport]) -> Result<usize, AocError> {
+  Ok(passports.iter().filter(|x| x.is_really_valid()).count())
 }
 
-pub fn part2(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(
+pub struct Day4;
+
+impl Day for Day4 {
+  type Model = Vec<Passport>;
+  const SOURCE: &'static str = include_str!("day4.rs");
+
+  fn day(&self) -> u32 {
+    4
+  }
+
+  fn title(&self) -> &'static str {
+    "Passport Processing"
+  }
+
+  fn parse(&self, input: &Input) -> Result<Self::Model, AocError> {
     parse_input(input)
-      .iter()
-      .filter(|x| x.is_really_valid())
-      .count() as i64,
-  )
+  }
+
+  fn part1(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part1(model)?.into())
+  }
+
+  fn part2(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part2(model)?.into())
+  }
+
+  // `size` passports, of which `invalid` percent fail part 2's checks. Every other one
+  // of those is missing a field, so fails part 1's as well, and the rest have one bad
+  // value
+  fn generate(&self, rng: &mut Rng, params: &Params) -> Option<String> {
+    let invalid = generate::invalid_count(params);
+    let mut cards: Vec<String> = (0..params.size.max(1))
+      .map(|i| {
+        let mut fields = valid_fields(rng);
+        if rng.one_in(2) {
+          fields.push(("cid", rng.range(100, 350).to_string()));
+        }
+        if i < invalid {
+          let field = rng.below(7) as usize;
+          if i % 2 == 0 {
+            fields.remove(field);
+          } else {
+            fields[field].1 = bad_value(rng, fields[field].0);
+          }
+        }
+        rng.shuffle(&mut fields);
+        // One to three lines per card
+        fields
+          .iter()
+          .map(|(key, value)| {
+            let separator = if rng.one_in(4) { '\n' } else { ' ' };
+            format!("{}:{}{}", key, value, separator)
+          })
+          .collect::<String>()
+          .trim_end()
+          .to_string()
+      })
+      .collect();
+    rng.shuffle(&mut cards);
+    Some(cards.join("\n\n") + "\n")
+  }
+}
+
+const EYE_COLORS: [&str; 7] = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+
+// Every required field, with a value that passes its check
+fn valid_fields(rng: &mut Rng) -> Vec<(&'static str, String)> {
+  let height = if rng.one_in(2) {
+    format!("{}cm", rng.range(150, 193))
+  } else {
+    format!("{}in", rng.range(59, 76))
+  };
+  vec![
+    ("byr", rng.range(1920, 2002).to_string()),
+    ("iyr", rng.range(2010, 2020).to_string()),
+    ("eyr", rng.range(2020, 2030).to_string()),
+    ("hgt", height),
+    ("hcl", format!("#{:06x}", rng.below(0x100_0000))),
+    ("ecl", String::from(*rng.pick(&EYE_COLORS))),
+    ("pid", format!("{:09}", rng.below(1_000_000_000))),
+  ]
+}
+
+// A value for `key` that fails its check
+fn bad_value(rng: &mut Rng, key: &str) -> String {
+  let either = |rng: &mut Rng, low: (i64, i64), high: (i64, i64)| {
+    let (from, to) = if rng.one_in(2) { low } else { high };
+    rng.range(from, to).to_string()
+  };
+  match (key, rng.below(3)) {
+    ("byr", _) => either(rng, (1900, 1919), (2003, 2030)),
+    ("iyr", _) => either(rng, (2000, 2009), (2021, 2030)),
+    ("eyr", _) => either(rng, (2010, 2019), (2031, 2040)),
+    ("hgt", 0) => format!("{}cm", either(rng, (100, 149), (194, 250))),
+    ("hgt", 1) => format!("{}in", either(rng, (40, 58), (77, 99))),
+    ("hgt", _) => rng.range(150, 193).to_string(),
+    ("hcl", 0) => format!("#{:05x}", rng.below(0x10_0000)),
+    ("hcl", 1) => format!("{:06x}", rng.below(0x100_0000)),
+    ("hcl", _) => String::from("#zzzzzz"),
+    ("ecl", _) => String::from(*rng.pick(&["xry", "zzz", "gr", "blue"])),
+    (_, 0) => format!("{:08}", rng.below(100_000_000)),
+    _ => format!("{:010}", rng.below(10_000_000_000)),
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  #[test]
+  fn test_generate() {
+    let params = Params {
+      size: 40,
+      invalid: 30.0,
+    };
+    let passports = parse(&Day4.generate(&mut Rng::new(1), &params).unwrap()).unwrap();
+    assert_eq!(40, passports.len());
+    // 12 invalid, half of them missing a field
+    assert_eq!(Ok(34), solve_part1(&passports));
+    assert_eq!(Ok(28), solve_part2(&passports));
+  }
+
+  #[test]
+  fn test_parse_input() {
+    let input = "ecl:gry pid:860033327\nbyr:1937\n\n\nhcl:#cfa07d eyr:2025\n";
+    let passports = parse(input).unwrap();
+    assert_eq!(2, passports.len());
+    assert_eq!(Some(String::from("1937")), passports[0].birth_year);
+
+    match parse("byr:1937\r\n\r\necl:gry foo:bar\r\n") {
+      Err(AocError::Parse { line, column, .. }) => assert_eq!((3, 9), (line, column)),
+      _ => panic!("expected a parse error"),
+    }
+  }
+
   #[test]
   fn test_height() {
     let valid = vec![Some(String::from("60in")), Some(String::from("190cm"))];
     let invalid = vec![None, Some(String::from("190in")), Some(String::from("190"))];
 
     for val in valid {
-      assert_eq!(true, validate_height(val))
+      assert!(validate_height(val))
     }
 
     for val in invalid {
-      assert_eq!(false, validate_height(val));
+      assert!(!validate_height(val));
     }
   }
 
@@ -210,11 +347,11 @@ mod tests {
       Some(String::from("#123abz")),
     ];
     for val in valid {
-      assert_eq!(true, validate_hair(val));
+      assert!(validate_hair(val));
     }
 
     for val in invalid {
-      assert_eq!(false, validate_hair(val));
+      assert!(!validate_hair(val));
     }
   }
 
@@ -233,11 +370,11 @@ mod tests {
     ];
 
     for val in valid {
-      assert_eq!(true, validate_number(val, 1920, 2002));
+      assert!(validate_number(val, 1920, 2002));
     }
 
     for val in invalid {
-      assert_eq!(false, validate_number(val, 1920, 2002));
+      assert!(!validate_number(val, 1920, 2002));
     }
   }
 
@@ -253,11 +390,11 @@ mod tests {
     ];
 
     for val in valid {
-      assert_eq!(true, validate_eye(Some(String::from(val))));
+      assert!(validate_eye(Some(String::from(val))));
     }
 
     for val in invalid {
-      assert_eq!(false, validate_eye(val));
+      assert!(!validate_eye(val));
     }
   }
 
@@ -272,11 +409,11 @@ mod tests {
     ];
 
     for val in valid {
-      assert_eq!(true, validate_pid(Some(String::from(val))));
+      assert!(validate_pid(Some(String::from(val))));
     }
 
     for val in invalid {
-      assert_eq!(false, validate_pid(val));
+      assert!(!validate_pid(val));
     }
   }
 }
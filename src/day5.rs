@@ -1,10 +1,13 @@
-use std::error::Error;
+use crate::generate::{self, Params};
+use crate::input::Input;
+use crate::rng::Rng;
+use crate::{Answer, AocError, Day};
 
 #[derive(Debug, PartialEq)]
-struct Pass {
-  row: i64,
-  column: i64,
-  id: i64,
+pub struct Pass {
+  pub row: i64,
+  pub column: i64,
+  pub id: i64,
 }
 
 fn construct_possibles() -> Vec<Pass> {
@@ -22,11 +25,32 @@ fn construct_possibles() -> Vec<Pass> {
   vec
 }
 
-fn parse_input(input: &str) -> Vec<Pass> {
-  input.lines().map(|x| parse_seat(x)).collect()
+fn parse_input(input: &Input) -> Result<Vec<Pass>, AocError> {
+  input.parse_lines(parse_seat)
 }
 
-fn parse_seat(input: &str) -> Pass {
+// A boarding pass like "FBFBBFFRLR": seven of F/B for the row and three of L/R for
+// the column
+pub fn parse_seat(input: &str) -> Result<Pass, AocError> {
+  for (i, c) in input.char_indices() {
+    let expected = if i < 7 { ['F', 'B'] } else { ['L', 'R'] };
+    if i >= 10 || !expected.contains(&c) {
+      let token = &input[i..i + c.len_utf8()];
+      return Err(AocError::parse(
+        input,
+        token,
+        "expected seven of F/B followed by three of L/R",
+      ));
+    }
+  }
+  if input.len() != 10 {
+    return Err(AocError::parse(
+      input,
+      &input[input.len()..],
+      "expected seven of F/B followed by three of L/R",
+    ));
+  }
+
   let row_code = &input[..7];
   let column_code = &input[7..];
   let mut row = 0;
@@ -43,24 +67,34 @@ fn parse_seat(input: &str) -> Pass {
     }
   }
 
-  Pass {
+  Ok(Pass {
     row,
     column,
     id: row * 8 + column,
-  }
+  })
 }
 
-pub fn part1(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(
-    parse_input(input)
-      .iter()
-      .fold(0, |acc, pass| i64::max(acc, pass.id)),
-  )
+// The boarding pass for a seat, the reverse of `parse_seat`
+pub fn encode_seat(row: i64, column: i64) -> String {
+  let bits = |value: i64, len: u32, zero: char, one: char| {
+    (0..len)
+      .rev()
+      .map(|i| if (value >> i) & 1 == 1 { one } else { zero })
+      .collect::<String>()
+  };
+  bits(row, 7, 'F', 'B') + &bits(column, 3, 'L', 'R')
+}
+
+pub fn parse(input: &str) -> Result<Vec<Pass>, AocError> {
+  parse_input(&Input::new(input))
 }
 
-pub fn part2(input: &str) -> Result<i64, Box<dyn Error>> {
+pub fn solve_part1(passes: &[Pass]) -> Result<i64, AocError> {
+  Ok(passes.iter().fold(0, |acc, pass| i64::max(acc, pass.id)))
+}
+
+pub fn solve_part2(actuals: &[Pass]) -> Result<i64, AocError> {
   let possibles = construct_possibles();
-  let actuals = parse_input(input);
 
   let mut potentials = Vec::new();
   for possible in possibles {
@@ -69,22 +103,69 @@ pub fn part2(input: &str) -> Result<i64, Box<dyn Error>> {
     }
   }
 
-  let mut found = 0;
+  let mut found = None;
 
   for potential in potentials {
     if actuals.iter().any(|x| x.id == potential.id + 1)
       && actuals.iter().any(|x| x.id == potential.id - 1)
     {
-      found = potential.id;
+      found = Some(potential.id);
     }
   }
 
-  Ok(found)
+  found.ok_or_else(|| AocError::NoSolution(String::from("no empty seat between two taken seats")))
+}
+
+pub struct Day5;
+
+impl Day for Day5 {
+  type Model = Vec<Pass>;
+  const SOURCE: &'static str = include_str!("day5.rs");
+
+  fn day(&self) -> u32 {
+    5
+  }
+
+  fn title(&self) -> &'static str {
+    "Binary Boarding"
+  }
+
+  fn parse(&self, input: &Input) -> Result<Self::Model, AocError> {
+    parse_input(input)
+  }
+
+  fn part1(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part1(model)?.into())
+  }
+
+  fn part2(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part2(model)?.into())
+  }
+
+  // `size` passes (2 to 1000) for a run of consecutive seats with one missing, which
+  // isn't in the last row or column since `solve_part2` never considers those
+  fn generate(&self, rng: &mut Rng, params: &Params) -> Option<String> {
+    let size = params.size.clamp(2, 1000) as i64;
+    let (first, missing) = loop {
+      let first = rng.range(0, 1023 - size);
+      let missing = first + rng.range(1, size - 1);
+      if missing % 8 != 7 && missing / 8 != 127 {
+        break (first, missing);
+      }
+    };
+    let mut passes: Vec<String> = (first..=first + size)
+      .filter(|&id| id != missing)
+      .map(|id| encode_seat(id / 8, id % 8))
+      .collect();
+    rng.shuffle(&mut passes);
+    Some(generate::lines(&passes))
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::property::{self, Ints, OneOf, Vecs};
 
   #[test]
   fn test_parse_seat() {
@@ -124,7 +205,59 @@ mod tests {
     ];
 
     for (code, pass) in tests {
-      assert_eq!(parse_seat(code), pass);
+      assert_eq!(code, encode_seat(pass.row, pass.column));
+      assert_eq!(parse_seat(code).unwrap(), pass);
+    }
+  }
+
+  // Every seat encodes to a pass that parses back to it, and every pass encodes back to
+  // itself
+  #[test]
+  fn test_seat_round_trip() {
+    let seats = (Ints { low: 0, high: 127 }, Ints { low: 0, high: 7 });
+    property::check(&seats, |&(row, column)| {
+      let code = encode_seat(row, column);
+      let id = row * 8 + column;
+      match parse_seat(&code) {
+        Ok(pass) if pass == Pass { row, column, id } => Ok(()),
+        other => Err(format!("{} parsed as {:?}", code, other)),
+      }
+    });
+
+    let codes = Vecs {
+      item: OneOf(vec![false, true]),
+      min: 10,
+      max: 10,
+    };
+    property::check(&codes, |bits| {
+      let code: String = bits
+        .iter()
+        .enumerate()
+        .map(|(i, &bit)| match (i < 7, bit) {
+          (true, false) => 'F',
+          (true, true) => 'B',
+          (false, false) => 'L',
+          (false, true) => 'R',
+        })
+        .collect();
+      let pass = parse_seat(&code).map_err(|e| format!("{}: {}", code, e))?;
+      match encode_seat(pass.row, pass.column) {
+        encoded if encoded == code => Ok(()),
+        encoded => Err(format!(
+          "{} parsed as {:?}, which encodes as {}",
+          code, pass, encoded
+        )),
+      }
+    });
+  }
+
+  #[test]
+  fn test_parse_seat_errors() {
+    for (code, column) in [("FBFBBFFRL", 10), ("FBFBBFFRLRR", 11), ("FBFXBFFRLR", 4)] {
+      match parse_seat(code) {
+        Err(AocError::Parse { column: c, .. }) => assert_eq!(column, c, "{}", code),
+        _ => panic!("expected a parse error for {}", code),
+      }
     }
   }
 }
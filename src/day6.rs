@@ -1,40 +1,56 @@
+use crate::generate::Params;
+use crate::input::Input;
+use crate::rng::Rng;
+use crate::{Answer, AocError, Day};
 use std::cell::RefCell;
 use std::collections::hash_set::HashSet;
-use std::error::Error;
 
 struct Answers {
   letter: char,
   count: usize,
 }
 
-fn parse_part_1(input: &str) -> usize {
-  let groups: Vec<&str> = input.split("\n\n").collect();
+// The questions each person in a group answered "yes" to, one string per person
+#[derive(Debug, PartialEq)]
+pub struct Group {
+  pub people: Vec<String>,
+}
+
+// Groups are separated by blank lines
+pub fn parse(input: &str) -> Result<Vec<Group>, AocError> {
+  Ok(parse_input(&Input::new(input)))
+}
+
+fn parse_input(input: &Input) -> Vec<Group> {
+  input
+    .paragraphs()
+    .iter()
+    .map(|group| Group {
+      people: group.lines.iter().map(|line| String::from(*line)).collect(),
+    })
+    .collect()
+}
+
+pub fn solve_part1(groups: &[Group]) -> Result<usize, AocError> {
   let mut total = 0;
   for group in groups {
     let mut set = HashSet::new();
-    for c in group.chars() {
-      if c == '\n' {
-        continue;
-      }
+    for c in group.people.iter().flat_map(|person| person.chars()) {
       set.insert(c);
     }
-    total += set.into_iter().count();
+    total += set.len();
   }
-  total
+  Ok(total)
 }
 
-fn parse_part_2(input: &str) -> usize {
-  let groups: Vec<&str> = input.split("\n\n").collect();
+pub fn solve_part2(groups: &[Group]) -> Result<usize, AocError> {
   let mut total = 0;
 
   for group in groups {
     let mut vec: Vec<RefCell<Answers>> = Vec::new();
-    let persons = group.split("\n").count();
+    let persons = group.people.len();
 
-    for c in group.chars() {
-      if c == '\n' {
-        continue;
-      }
+    for c in group.people.iter().flat_map(|person| person.chars()) {
       if let Some(answer) = vec.iter().find(|x| x.borrow().letter == c) {
         answer.borrow_mut().count += 1;
       } else {
@@ -47,44 +63,118 @@ fn parse_part_2(input: &str) -> usize {
     total += vec.iter().filter(|x| x.borrow().count == persons).count();
   }
 
-  total
+  Ok(total)
 }
 
-pub fn part1(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(parse_part_1(input) as i64)
-}
+pub struct Day6;
 
-pub fn part2(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(parse_part_2(input) as i64)
-}
+impl Day for Day6 {
+  type Model = Vec<Group>;
+  const SOURCE: &'static str = include_str!("day6.rs");
 
-#[cfg(test)]
-mod tests {
-  use super::*;
+  fn day(&self) -> u32 {
+    6
+  }
+
+  fn title(&self) -> &'static str {
+    "Custom Customs"
+  }
 
-  static INPUT: &str = r"abc
+  fn parse(&self, input: &Input) -> Result<Self::Model, AocError> {
+    Ok(parse_input(input))
+  }
 
-a
-b
-c
+  fn part1(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part1(model)?.into())
+  }
 
-ab
-ac
+  fn part2(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part2(model)?.into())
+  }
 
-a
-a
-a
-a
+  // `size` groups of one to five people, who share a few answers and add some of their
+  // own
+  fn generate(&self, rng: &mut Rng, params: &Params) -> Option<String> {
+    let groups: Vec<String> = (0..params.size.max(1))
+      .map(|_| {
+        let mut letters: Vec<char> = ('a'..='z').collect();
+        rng.shuffle(&mut letters);
+        let shared = rng.range(0, 5) as usize;
+        (0..rng.range(1, 5))
+          .map(|_| {
+            let mut person: Vec<char> = letters[..shared].to_vec();
+            person.extend(letters[shared..].iter().filter(|_| rng.one_in(6)));
+            if person.is_empty() {
+              person.push(*rng.pick(&letters));
+            }
+            rng.shuffle(&mut person);
+            person.into_iter().collect::<String>() + "\n"
+          })
+          .collect()
+      })
+      .collect();
+    Some(groups.join("\n"))
+  }
+}
 
-b";
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::examples::Example;
+  use crate::property::{self, Ints, Vecs};
+  use std::collections::BTreeSet;
 
+  // A question everyone in a group answered is one that anyone in it answered
   #[test]
-  fn test_part_1() {
-    assert_eq!(11, parse_part_1(INPUT));
+  fn test_everyone_at_most_anyone() {
+    let person = Vecs {
+      item: Ints { low: 0, high: 25 },
+      min: 1,
+      max: 8,
+    };
+    let groups = Vecs {
+      item: Vecs {
+        item: person,
+        min: 1,
+        max: 5,
+      },
+      min: 1,
+      max: 8,
+    };
+    property::check(&groups, |groups| {
+      let groups: Vec<String> = groups
+        .iter()
+        .map(|group| {
+          group
+            .iter()
+            .map(|person| {
+              let letters: BTreeSet<char> =
+                person.iter().map(|&i| (b'a' + i as u8) as char).collect();
+              letters.into_iter().collect::<String>() + "\n"
+            })
+            .collect()
+        })
+        .collect();
+      let input = groups.join("\n");
+      let groups = parse(&input).map_err(|e| e.to_string())?;
+      let anyone = solve_part1(&groups).map_err(|e| e.to_string())?;
+      let everyone = solve_part2(&groups).map_err(|e| e.to_string())?;
+      match everyone <= anyone {
+        true => Ok(()),
+        false => Err(format!(
+          "{} answered by everyone, but {} by anyone",
+          everyone, anyone
+        )),
+      }
+    });
   }
 
   #[test]
-  fn test_part_2() {
-    assert_eq!(6, parse_part_2(INPUT));
+  fn test_crlf() {
+    let input = Example::load(6, "1").unwrap().input;
+    let groups = parse(&input.replace('\n', "\r\n")).unwrap();
+    assert_eq!(5, groups.len());
+    assert_eq!(Ok(11), solve_part1(&groups));
+    assert_eq!(Ok(6), solve_part2(&groups));
   }
 }
@@ -1,13 +1,20 @@
+use crate::cancel;
+use crate::error::parse_number;
+use crate::generate::{self, Params};
+use crate::input::Input;
+use crate::rng::Rng;
+use crate::trace;
+use crate::{Answer, AocError, Day};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::error::Error;
 
+// A bag like "shiny gold": its prefix is "shiny" and its color "gold"
 #[derive(Debug, Eq, Hash, PartialEq)]
 pub struct Bag {
-  prefix: String,
-  color: String,
+  pub prefix: String,
+  pub color: String,
 }
 
 impl Bag {
@@ -23,68 +30,134 @@ lazy_static! {
       .unwrap();
 }
 
-// Parses into an adjacency list - used exclusively in part 2
-fn parse_input_list(input: &str) -> Vec<(Bag, Vec<(Bag, usize)>)> {
-  let lines: Vec<&str> = input.lines().collect();
-  let mut all_edges = Vec::new();
+// Each bag alongside the bags (and how many of each) it must contain
+pub type AdjacencyList = Vec<(Bag, Vec<(Bag, usize)>)>;
 
-  for line in lines {
-    let split_index = line.find("contain").unwrap();
+// Parses into an adjacency list, one entry per rule
+fn parse_input_list(input: &Input) -> Result<AdjacencyList, AocError> {
+  let all_edges = input.parse_lines(|line| {
+    let split_index = line
+      .find("contain")
+      .ok_or_else(|| AocError::parse(line, line, "expected \"contain\""))?;
     let (left, right) = line.split_at(split_index);
 
     // Begin parsing left
-    let bag = parse_left(left);
-    let edges = parse_right(right);
-    all_edges.push((bag, edges));
+    let bag = parse_left(line, left)?;
+    let edges = parse_right(line, right)?;
+    Ok((bag, edges))
+  })?;
+
+  // Every bag that is contained by another needs a rule of its own
+  let known: HashSet<String> = all_edges.iter().map(|(bag, _)| bag.hash()).collect();
+  let lines: Vec<&str> = input.lines().collect();
+  for (i, (line, (_, edges))) in lines.iter().zip(&all_edges).enumerate() {
+    for (bag, _) in edges {
+      if !known.contains(&bag.hash()) {
+        let (token, name) = bag_token(line, bag);
+        let message = format!("no rule for {} bags", name);
+        return Err(AocError::parse(line, token, message).at_line(i + 1));
+      }
+    }
+  }
+
+  // Part 2 counts the bags inside each bag, which never ends if a bag is inside itself
+  if let Some((rule, edge)) = find_cycle(&all_edges) {
+    let line = lines[rule];
+    let (token, name) = bag_token(line, &all_edges[rule].1[edge].0);
+    let message = format!("{} bags end up inside themselves", name);
+    return Err(AocError::parse(line, token, message).at_line(rule + 1));
   }
 
-  all_edges
+  Ok(all_edges)
 }
 
-fn parse_left(input: &str) -> Bag {
-  let captures = LEFT_REGEX.captures(input).unwrap();
-  let prefix = captures
-    .get(1)
-    .or_else(|| panic!("Could not find prefix"))
-    .map(|x| String::from(x.as_str()))
-    .unwrap();
-  let color = captures
-    .get(2)
-    .or_else(|| panic!("Could not find color"))
-    .map(|x| String::from(x.as_str()))
-    .unwrap();
+// Where `bag` is named among the bags that `line` contains, and its name
+fn bag_token<'a>(line: &'a str, bag: &Bag) -> (&'a str, String) {
+  let name = format!("{} {}", bag.prefix, bag.color);
+  let start = line.find("contain").unwrap_or(0);
+  let token = line[start..].find(&name).map_or(line, |index| {
+    &line[start + index..start + index + name.len()]
+  });
+  (token, name)
+}
 
-  Bag { prefix, color }
+// The (rule, edge) that closes a loop of bags inside each other, if there is one. A
+// depth-first search with its own stack, since rules can chain thousands deep
+fn find_cycle(list: &[(Bag, Vec<(Bag, usize)>)]) -> Option<(usize, usize)> {
+  let index: HashMap<String, usize> = list
+    .iter()
+    .enumerate()
+    .map(|(i, (bag, _))| (bag.hash(), i))
+    .collect();
+  // Whether each rule is on the current path, or has been searched completely
+  let mut on_path = vec![false; list.len()];
+  let mut done = vec![false; list.len()];
+  for start in 0..list.len() {
+    if done[start] {
+      continue;
+    }
+    on_path[start] = true;
+    let mut stack = vec![(start, 0)];
+    while let Some((rule, edge)) = stack.pop() {
+      let (bag, _) = match list[rule].1.get(edge) {
+        Some(contained) => contained,
+        None => {
+          on_path[rule] = false;
+          done[rule] = true;
+          continue;
+        }
+      };
+      stack.push((rule, edge + 1));
+      let next = index[&bag.hash()];
+      if on_path[next] {
+        return Some((rule, edge));
+      }
+      if !done[next] {
+        on_path[next] = true;
+        stack.push((next, 0));
+      }
+    }
+  }
+  None
+}
+
+fn parse_left(line: &str, input: &str) -> Result<Bag, AocError> {
+  let captures = LEFT_REGEX
+    .captures(input)
+    .ok_or_else(|| AocError::parse(line, input, "expected a bag like \"light red bags\""))?;
+  let prefix = String::from(&captures[1]);
+  let color = String::from(&captures[2]);
+
+  Ok(Bag { prefix, color })
 }
 
-fn parse_right(input: &str) -> Vec<(Bag, usize)> {
+fn parse_right(line: &str, input: &str) -> Result<Vec<(Bag, usize)>, AocError> {
   let mut result = Vec::new();
 
-  for line in input.split(", ") {
-    let captures = RIGHT_REGEX.captures_iter(line);
-    for capture in captures {
-      let count: usize = capture
-        .name("count")
-        .map(|x| x.as_str().parse().unwrap())
-        .unwrap();
-      let prefix = capture
-        .name("prefix")
-        .map(|x| String::from(x.as_str()))
-        .unwrap();
-      let color = capture
-        .name("color")
-        .map(|x| String::from(x.as_str()))
-        .unwrap();
-      result.push((Bag { prefix, color }, count))
+  for part in input.split(", ") {
+    if part == "contain no other bags." {
+      continue;
     }
+    let capture = RIGHT_REGEX.captures(part).ok_or_else(|| {
+      AocError::parse(
+        line,
+        part,
+        "expected a count and bag like \"2 muted yellow bags\"",
+      )
+    })?;
+    let count = capture.name("count").unwrap();
+    let count: usize = parse_number(line, &part[count.range()], "a count")?;
+    let prefix = String::from(&capture["prefix"]);
+    let color = String::from(&capture["color"]);
+    result.push((Bag { prefix, color }, count))
   }
 
-  result
+  Ok(result)
 }
 
 // Parses an adjacency list into a matrix - used for part 1
-pub fn list_to_matrix<'a>(
-  adj_list: Vec<(Bag, Vec<(Bag, usize)>)>,
+pub fn list_to_matrix(
+  adj_list: &[(Bag, Vec<(Bag, usize)>)],
 ) -> (HashMap<String, usize>, Vec<Vec<bool>>) {
   let len = adj_list.len();
   let mut matrix = vec![vec![false; len]; len];
@@ -106,8 +179,15 @@ pub fn list_to_matrix<'a>(
   (map, matrix)
 }
 
-pub fn total(key: String, map: HashMap<String, usize>, matrix: Vec<Vec<bool>>) -> usize {
-  let &index = map.get(&key).unwrap();
+pub fn total(
+  key: String,
+  map: HashMap<String, usize>,
+  matrix: Vec<Vec<bool>>,
+) -> Result<usize, AocError> {
+  let _span = trace::span("day7::total");
+  let &index = map
+    .get(&key)
+    .ok_or_else(|| AocError::NoSolution(format!("no rule for bag {:?}", key)))?;
   let mut set: HashSet<usize> = HashSet::new();
   let mut queue: Vec<usize> = Vec::new();
 
@@ -122,8 +202,8 @@ pub fn total(key: String, map: HashMap<String, usize>, matrix: Vec<Vec<bool>>) -
   }
 
   // Exhaust queue
-  while queue.len() > 0 {
-    let index = queue.pop().unwrap();
+  while let Some(index) = queue.pop() {
+    trace::count("bags visited");
     for (row_index, row) in matrix.iter().enumerate() {
       if row[index] {
         if set.contains(&row_index) {
@@ -136,52 +216,56 @@ pub fn total(key: String, map: HashMap<String, usize>, matrix: Vec<Vec<bool>>) -
     }
   }
 
-  num_found
+  Ok(num_found)
 }
 
-fn parse_part_1(input: &str) -> usize {
-  let adj_list = parse_input_list(input);
-  let (map, matrix) = list_to_matrix(adj_list);
-  total(String::from("shinygold"), map, matrix)
+pub fn parse(input: &str) -> Result<AdjacencyList, AocError> {
+  parse_input_list(&Input::new(input))
 }
 
-pub fn part1(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(parse_part_1(input) as i64)
+// How many bags can eventually contain a shiny gold bag
+pub fn solve_part1(adj_list: &[(Bag, Vec<(Bag, usize)>)]) -> Result<usize, AocError> {
+  let (map, matrix) = list_to_matrix(adj_list);
+  total(String::from("shinygold"), map, matrix)
 }
 
 // Recursively returns the amount of bags that the given bag can include (including itself)
 fn calculate(
   // cache required for storage of previously executed calculations
   cache: &mut HashMap<String, usize>,
-  list: &Vec<(Bag, Vec<(Bag, usize)>)>,
+  list: &[(Bag, Vec<(Bag, usize)>)],
   key: String,
-) -> usize {
+) -> Result<usize, AocError> {
   if let Some(&val) = cache.get(&key) {
-    return val;
+    return Ok(val);
   }
+  cancel::check()?;
 
   // One for the current bag
   let mut total = 1;
-  let (_, edges) = list.into_iter().find(|(bag, _)| bag.hash() == key).unwrap();
+  let (_, edges) = list
+    .iter()
+    .find(|(bag, _)| bag.hash() == key)
+    .ok_or_else(|| AocError::NoSolution(format!("no rule for bag {:?}", key)))?;
 
+  let overflow = || AocError::NoSolution(format!("the bags inside {:?} overflowed", key));
   for (bag, count) in edges {
-    total += count * calculate(cache, list, bag.hash());
+    let inside = count
+      .checked_mul(calculate(cache, list, bag.hash())?)
+      .ok_or_else(overflow)?;
+    total = inside.checked_add(total).ok_or_else(overflow)?;
   }
 
   cache.insert(key, total);
-  total
+  Ok(total)
 }
 
-fn parse_part_2(input: &str) -> usize {
+// How many bags a shiny gold bag must contain
+pub fn solve_part2(adj_list: &[(Bag, Vec<(Bag, usize)>)]) -> Result<usize, AocError> {
   let mut cache: HashMap<String, usize> = HashMap::new();
-  let adj_list = parse_input_list(input);
 
   // Subtracting one because we're not counting the bag we're using
-  calculate(&mut cache, &adj_list, String::from("shinygold")) - 1
-}
-
-pub fn part2(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(parse_part_2(input) as i64)
+  Ok(calculate(&mut cache, adj_list, String::from("shinygold"))? - 1)
 }
 
 // Useful for debugging purposes
@@ -198,7 +282,7 @@ pub fn print_data(map: HashMap<String, usize>, matrix: Vec<Vec<bool>>) {
   for i in 0..len {
     print!("{} ", i);
   }
-  print!("\n");
+  println!();
   for (x, row) in matrix.iter().enumerate() {
     print!("{} ", x);
     for &val in row {
@@ -208,40 +292,199 @@ pub fn print_data(map: HashMap<String, usize>, matrix: Vec<Vec<bool>>) {
         print!("0 ");
       }
     }
-    print!("\n");
+    println!();
   }
 }
 
+pub struct Day7;
+
+impl Day for Day7 {
+  type Model = AdjacencyList;
+  const SOURCE: &'static str = include_str!("day7.rs");
+
+  fn day(&self) -> u32 {
+    7
+  }
+
+  fn title(&self) -> &'static str {
+    "Handy Haversacks"
+  }
+
+  fn parse(&self, input: &Input) -> Result<Self::Model, AocError> {
+    parse_input_list(input)
+  }
+
+  fn part1(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part1(model)?.into())
+  }
+
+  fn part2(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part2(model)?.into())
+  }
+
+  // `size` rules (2 to 900) in a random order, where each bag only holds bags further
+  // down a random ranking, so they never contain themselves. Shiny gold is one of the
+  // last few, to keep the number it holds reasonable, and at least one bag holds it.
+  fn generate(&self, rng: &mut Rng, params: &Params) -> Option<String> {
+    let mut names: Vec<String> = ADJECTIVES
+      .iter()
+      .flat_map(|adjective| {
+        COLORS
+          .iter()
+          .map(move |color| format!("{} {}", adjective, color))
+      })
+      .filter(|name| name != "shiny gold")
+      .collect();
+    rng.shuffle(&mut names);
+    let n = params.size.clamp(2, names.len() + 1);
+    names.truncate(n - 1);
+    let gold = n - 1 - rng.below(n.min(7) as u64) as usize;
+    names.insert(gold, String::from("shiny gold"));
+
+    let mut contents: Vec<Vec<(usize, i64)>> = (0..n)
+      .map(|i| {
+        let mut inside: Vec<usize> = (0..rng.below(4))
+          .filter(|_| i + 1 < n)
+          .map(|_| rng.range(i as i64 + 1, n as i64 - 1) as usize)
+          .collect();
+        inside.sort_unstable();
+        inside.dedup();
+        inside.iter().map(|&j| (j, rng.range(1, 5))).collect()
+      })
+      .collect();
+    if gold > 0 {
+      let holder = rng.below(gold as u64) as usize;
+      if !contents[holder].iter().any(|&(j, _)| j == gold) {
+        contents[holder].push((gold, rng.range(1, 5)));
+      }
+    }
+
+    let mut rules: Vec<String> = contents
+      .iter()
+      .enumerate()
+      .map(|(i, inside)| {
+        let inside: Vec<String> = inside
+          .iter()
+          .map(|&(j, count)| {
+            let plural = if count == 1 { "" } else { "s" };
+            format!("{} {} bag{}", count, names[j], plural)
+          })
+          .collect();
+        let inside = if inside.is_empty() {
+          String::from("no other bags")
+        } else {
+          inside.join(", ")
+        };
+        format!("{} bags contain {}.", names[i], inside)
+      })
+      .collect();
+    rng.shuffle(&mut rules);
+    Some(generate::lines(&rules))
+  }
+}
+
+const ADJECTIVES: [&str; 30] = [
+  "bold", "bright", "calm", "clear", "dark", "dashed", "dim", "dotted", "drab", "dull", "dusty",
+  "faded", "frosted", "glossy", "hazy", "light", "matte", "mirrored", "muted", "pale", "pastel",
+  "plaid", "posh", "rusty", "shiny", "soft", "striped", "vibrant", "vivid", "wavy",
+];
+
+const COLORS: [&str; 30] = [
+  "aqua",
+  "beige",
+  "black",
+  "blue",
+  "bronze",
+  "brown",
+  "chartreuse",
+  "coral",
+  "crimson",
+  "cyan",
+  "fuchsia",
+  "gold",
+  "gray",
+  "green",
+  "indigo",
+  "lavender",
+  "lime",
+  "magenta",
+  "maroon",
+  "olive",
+  "orange",
+  "plum",
+  "purple",
+  "red",
+  "salmon",
+  "silver",
+  "tan",
+  "teal",
+  "tomato",
+  "white",
+];
+
 #[cfg(test)]
 mod tests {
   use super::*;
-
-  static INPUT_PART_1: &str = r"light red bags contain 1 bright white bag, 2 muted yellow bags.
-dark orange bags contain 3 bright white bags, 4 muted yellow bags.
-bright white bags contain 1 shiny gold bag.
-muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
-shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
-dark olive bags contain 3 faded blue bags, 4 dotted black bags.
-vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
-faded blue bags contain no other bags.
-dotted black bags contain no other bags.";
+  use crate::examples::Example;
 
   #[test]
-  fn test_part1() {
-    assert_eq!(4, parse_part_1(INPUT_PART_1));
+  fn test_parse() {
+    let rules = parse(&Example::load(7, "1").unwrap().input).unwrap();
+    let (bag, edges) = &rules[0];
+    assert_eq!(("light", "red"), (&bag.prefix[..], &bag.color[..]));
+    assert_eq!(2, edges.len());
+    assert_eq!(
+      ("muted", "yellow", 2),
+      (&edges[1].0.prefix[..], &edges[1].0.color[..], edges[1].1)
+    );
   }
 
-  static INPUT_PART_2: &str = r"shiny gold bags contain 2 dark red bags.
-dark red bags contain 2 dark orange bags.
-dark orange bags contain 2 dark yellow bags.
-dark yellow bags contain 2 dark green bags.
-dark green bags contain 2 dark blue bags.
-dark blue bags contain 2 dark violet bags.
-dark violet bags contain no other bags.";
-
   #[test]
-  fn test_part2() {
-    assert_eq!(32, parse_part_2(INPUT_PART_1));
-    assert_eq!(126, parse_part_2(INPUT_PART_2));
+  fn test_parse_errors() {
+    let missing = "shiny gold bags contain 2 dark red bags.";
+    match parse(missing) {
+      Err(AocError::Parse {
+        line,
+        column,
+        message,
+        ..
+      }) => assert_eq!(
+        (1, 27, "no rule for dark red bags"),
+        (line, column, &message[..])
+      ),
+      other => panic!("expected a parse error, got {:?}", other.err()),
+    }
+
+    let garbled =
+      "dark red bags contain no other bags.\nshiny gold bags contain two dark red bags.";
+    match parse(garbled) {
+      Err(AocError::Parse { line, column, .. }) => assert_eq!((2, 17), (line, column)),
+      other => panic!("expected a parse error, got {:?}", other.err()),
+    }
+
+    for (text, expected) in &[
+      ("shiny gold bags contain 1 shiny gold bag.", (1, 27)),
+      (
+        "shiny gold bags contain 2 dark red bags.\ndark red bags contain 1 shiny gold bag.",
+        (2, 25),
+      ),
+    ] {
+      match parse(text) {
+        Err(AocError::Parse {
+          line,
+          column,
+          message,
+          ..
+        }) => {
+          assert_eq!(*expected, (line, column));
+          assert!(
+            message.ends_with("bags end up inside themselves"),
+            "{}",
+            message
+          );
+        }
+        other => panic!("expected a parse error, got {:?}", other.err()),
+      }
+    }
   }
 }
@@ -1,143 +1,241 @@
+use crate::cancel;
+use crate::error::parse_number;
+use crate::generate::{self, Params};
+use crate::input::Input;
+use crate::rng::Rng;
+use crate::{Answer, AocError, Day};
 use std::collections::HashSet;
-use std::error::Error;
+use std::convert::TryFrom;
 
-#[derive(Clone)]
-enum Action {
+// One instruction of the boot code
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
   Noop(i64),
   Jump(i64),
   Acc(i64),
 }
 
-fn parse_line(input: &str) -> Action {
-  let sections: Vec<&str> = input.split(" ").collect();
-  let num: i64 = sections[1].parse().unwrap();
+fn parse_line(input: &str) -> Result<Action, AocError> {
+  let sections: Vec<&str> = input.split(' ').collect();
+  if sections.len() != 2 {
+    return Err(AocError::parse(
+      input,
+      input,
+      "expected an instruction like \"acc +1\"",
+    ));
+  }
+  let num: i64 = parse_number(input, sections[1], "an argument")?;
   match sections[0] {
-    "nop" => Action::Noop(num),
-    "acc" => Action::Acc(num),
-    "jmp" => Action::Jump(num),
-    _ => panic!("Cannot parse input"),
+    "nop" => Ok(Action::Noop(num)),
+    "acc" => Ok(Action::Acc(num)),
+    "jmp" => Ok(Action::Jump(num)),
+    other => Err(AocError::parse(
+      input,
+      other,
+      format!("unknown operation {:?}", other),
+    )),
   }
 }
 
-fn parse_part_1(input: &str) -> i64 {
-  let program: Vec<Action> = input.lines().map(parse_line).collect();
+pub fn parse(input: &str) -> Result<Vec<Action>, AocError> {
+  Input::new(input).parse_lines(parse_line)
+}
+
+// The accumulator just before any instruction runs a second time
+pub fn solve_part1(program: &[Action]) -> Result<i64, AocError> {
   let mut visited: HashSet<i64> = HashSet::new();
   let mut cursor: i64 = 0;
   let mut acc: i64 = 0;
 
   loop {
+    cancel::check()?;
     if visited.contains(&cursor) {
       break;
     }
     visited.insert(cursor);
-    let action = &program[cursor as usize];
-    match action {
-      &Action::Noop(_) => {
-        cursor += 1;
-        continue;
-      }
-      &Action::Jump(val) => {
-        cursor += val;
-        continue;
-      }
-      &Action::Acc(val) => {
-        acc += val;
-        cursor += 1;
-      }
-    }
+    let action = fetch(program, cursor).ok_or_else(|| {
+      AocError::NoSolution(String::from(
+        "the program left its instructions without repeating one",
+      ))
+    })?;
+    let (next, total) = step(action, cursor, acc)?;
+    cursor = next;
+    acc = total;
   }
 
-  acc
+  Ok(acc)
 }
 
-pub fn part1(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(parse_part_1(input) as i64)
+fn fetch(program: &[Action], cursor: i64) -> Option<&Action> {
+  usize::try_from(cursor)
+    .ok()
+    .and_then(|index| program.get(index))
+}
+
+// Runs one instruction, giving the next cursor and accumulator
+fn step(action: &Action, cursor: i64, acc: i64) -> Result<(i64, i64), AocError> {
+  let overflow = || AocError::NoSolution(String::from("the cursor or accumulator overflowed"));
+  match *action {
+    Action::Noop(_) => Ok((cursor.checked_add(1).ok_or_else(overflow)?, acc)),
+    Action::Jump(val) => Ok((cursor.checked_add(val).ok_or_else(overflow)?, acc)),
+    Action::Acc(val) => Ok((
+      cursor.checked_add(1).ok_or_else(overflow)?,
+      acc.checked_add(val).ok_or_else(overflow)?,
+    )),
+  }
 }
 
-fn does_execute(program: &Vec<Action>) -> (bool, i64) {
+fn does_execute(program: &[Action]) -> Result<(bool, i64), AocError> {
   let mut acc: i64 = 0;
   let mut cursor: i64 = 0;
   let mut visited: HashSet<i64> = HashSet::new();
 
   loop {
-    if let None = program.get(cursor as usize) {
-      return (true, acc);
-    }
-
-    if visited.contains(&cursor) {
-      return (false, 0);
+    cancel::check()?;
+    // Only running off the end counts as terminating, not a jump anywhere else
+    if cursor == program.len() as i64 {
+      return Ok((true, acc));
     }
+    let action = match fetch(program, cursor) {
+      Some(action) if !visited.contains(&cursor) => action,
+      _ => return Ok((false, 0)),
+    };
     visited.insert(cursor);
-    let action = &program[cursor as usize];
-    match action {
-      &Action::Noop(_) => {
-        cursor += 1;
-        continue;
-      }
-      &Action::Jump(val) => {
-        cursor += val;
-        continue;
-      }
-      &Action::Acc(val) => {
-        acc += val;
-        cursor += 1;
-      }
-    }
+    let (next, total) = step(action, cursor, acc)?;
+    cursor = next;
+    acc = total;
   }
 }
 
-fn parse_part_2(input: &str) -> i64 {
-  let main: Vec<Action> = input.lines().map(parse_line).collect();
-  let mut changed: Vec<Action> = main.clone();
+// The accumulator once the program terminates, after swapping one nop or jmp
+pub fn solve_part2(main: &[Action]) -> Result<i64, AocError> {
+  let mut changed: Vec<Action> = main.to_vec();
   for (i, action) in main.iter().enumerate() {
-    match action {
-      &Action::Noop(val) => {
+    match *action {
+      Action::Noop(val) => {
         changed[i] = Action::Jump(val);
-        let (executes, result) = does_execute(&changed);
+        let (executes, result) = does_execute(&changed)?;
         if executes {
-          return result;
+          return Ok(result);
         }
         changed[i] = Action::Noop(val);
       }
-      &Action::Jump(val) => {
+      Action::Jump(val) => {
         changed[i] = Action::Noop(val);
-        let (executes, result) = does_execute(&changed);
+        let (executes, result) = does_execute(&changed)?;
         if executes {
-          return result;
+          return Ok(result);
         }
         changed[i] = Action::Jump(val);
       }
-      &Action::Acc(_) => continue,
+      Action::Acc(_) => continue,
     }
   }
-  panic!("No result found!");
+  Err(AocError::NoSolution(String::from(
+    "no single nop/jmp swap lets the program terminate",
+  )))
 }
 
-pub fn part2(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(parse_part_2(input) as i64)
+pub struct Day8;
+
+impl Day for Day8 {
+  type Model = Vec<Action>;
+  const SOURCE: &'static str = include_str!("day8.rs");
+
+  fn day(&self) -> u32 {
+    8
+  }
+
+  fn title(&self) -> &'static str {
+    "Handheld Halting"
+  }
+
+  fn parse(&self, input: &Input) -> Result<Self::Model, AocError> {
+    input.parse_lines(parse_line)
+  }
+
+  fn part1(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part1(model)?.into())
+  }
+
+  fn part2(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part2(model)?.into())
+  }
+
+  // `size` instructions (1 to 10000) that would run off the end, jumping over blocks of
+  // random dead code, except that one `nop` on the way has become a `jmp` back to
+  // somewhere already run. So the program loops, and swapping that `jmp` back fixes it.
+  fn generate(&self, rng: &mut Rng, params: &Params) -> Option<String> {
+    let n = params.size.clamp(1, 10_000);
+    let mut program: Vec<(&str, i64)> = Vec::with_capacity(n);
+    // The instructions the fixed program runs, and those of them that aren't jumps
+    let mut run = Vec::new();
+    let mut straight = Vec::new();
+    while program.len() < n {
+      let left = n - program.len();
+      run.push(program.len());
+      if !straight.is_empty() && left > 1 && rng.one_in(4) {
+        let dead = rng.range(1, left.min(6) as i64 - 1);
+        program.push(("jmp", dead + 1));
+        for _ in 0..dead {
+          program.push((*rng.pick(&["acc", "jmp", "nop"]), rng.range(-50, 50)));
+        }
+      } else {
+        straight.push(program.len());
+        let operation = if rng.one_in(3) { "nop" } else { "acc" };
+        program.push((operation, rng.range(-50, 50)));
+      }
+    }
+    let broken = *rng.pick(&straight);
+    let target = *rng.pick(&run[..=run.iter().position(|&i| i == broken).unwrap()]);
+    program[broken] = ("jmp", target as i64 - broken as i64);
+    let lines: Vec<String> = program
+      .iter()
+      .map(|(operation, argument)| format!("{} {:+}", operation, argument))
+      .collect();
+    Some(generate::lines(&lines))
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::examples::Example;
 
-  static INPUT_PART_1: &str = r"nop +0
-acc +1
-jmp +4
-acc +3
-jmp -3
-acc -99
-acc +1
-jmp -4
-acc +6";
+  #[test]
+  fn test_parse() {
+    let program = parse(&Example::load(8, "1").unwrap().input).unwrap();
+    assert_eq!(Action::Jump(4), program[2]);
+  }
 
   #[test]
-  fn test_part1() {
-    assert_eq!(5, parse_part_1(INPUT_PART_1));
+  fn test_parse_line() {
+    match parse_line("jmp +x") {
+      Err(AocError::Parse { column, .. }) => assert_eq!(5, column),
+      _ => panic!("expected a parse error"),
+    }
+    match parse_line("hcf +1") {
+      Err(AocError::Parse { column, .. }) => assert_eq!(1, column),
+      _ => panic!("expected a parse error"),
+    }
   }
 
   #[test]
-  fn test_part2() {
-    assert_eq!(8, parse_part_2(INPUT_PART_1));
+  fn test_overflow() {
+    for text in &[
+      "nop +0\njmp +9223372036854775807",
+      "acc +9223372036854775807\nacc +1\njmp -2",
+    ] {
+      let program = parse(text).unwrap();
+      match solve_part1(&program) {
+        Err(AocError::NoSolution(_)) => (),
+        other => panic!("{:?}: expected no solution, found {:?}", text, other),
+      }
+    }
+    let program = parse("jmp +5\njmp -1").unwrap();
+    assert!(matches!(
+      solve_part2(&program),
+      Err(AocError::NoSolution(_))
+    ));
   }
 }
@@ -1,9 +1,13 @@
-use std::error::Error;
+use crate::error::parse_number;
+use crate::generate::{self, Params};
+use crate::input::Input;
+use crate::rng::Rng;
+use crate::{Answer, AocError, Day};
 
 fn can_add(summers: &[i64], total: i64) -> bool {
   for i in 0..summers.len() {
     for ii in i + 1..summers.len() {
-      if summers[i] + summers[ii] == total {
+      if summers[i].checked_add(summers[ii]) == Some(total) {
         return true;
       }
     }
@@ -11,23 +15,27 @@ fn can_add(summers: &[i64], total: i64) -> bool {
   false
 }
 
-fn can_contiguously_add(summers: &[i64], total: i64) -> (usize, usize) {
+fn can_contiguously_add(summers: &[i64], total: i64) -> Option<(usize, usize)> {
   for i in 0..summers.len() {
     let mut running_total = summers[i];
     let mut cursor = i + 1;
-    while running_total < total {
-      running_total += summers[cursor];
+    while running_total < total && cursor < summers.len() {
+      // A range that overflows on the way can't come back down to `total`
+      running_total = match running_total.checked_add(summers[cursor]) {
+        Some(running_total) => running_total,
+        None => break,
+      };
       cursor += 1;
     }
     if running_total == total {
-      return (i, cursor);
+      return Some((i, cursor));
     }
   }
 
-  panic!("Not found!");
+  None
 }
 
-fn add_smallest_largest(range: &[i64]) -> i64 {
+fn add_smallest_largest(range: &[i64]) -> Result<i64, AocError> {
   let mut smallest = range[0];
   let mut largest = range[0];
   for &num in range {
@@ -38,18 +46,28 @@ fn add_smallest_largest(range: &[i64]) -> i64 {
       smallest = num;
     }
   }
-  largest + smallest
+  largest
+    .checked_add(smallest)
+    .ok_or_else(|| AocError::NoSolution(String::from("the encryption weakness overflowed")))
 }
 
-fn parse_part_2(input: &str, target: i64) -> i64 {
-  let numbers: Vec<i64> = input.lines().map(|x| x.parse().unwrap()).collect();
-  let (start, end) = can_contiguously_add(&numbers, target);
-  add_smallest_largest(&numbers[start..end])
+fn parse_input(input: &Input) -> Result<Vec<i64>, AocError> {
+  input.parse_lines(|line| parse_number(line, line, "a number"))
 }
 
-fn parse_part_1(input: &str, pre: usize) -> i64 {
-  let numbers: Vec<i64> = input.lines().map(|x| x.parse().unwrap()).collect();
+pub fn parse(input: &str) -> Result<Vec<i64>, AocError> {
+  parse_input(&Input::new(input))
+}
 
+// The sum of the smallest and largest of a contiguous range that sums to `target`
+fn encryption_weakness(numbers: &[i64], target: i64) -> Result<i64, AocError> {
+  let (start, end) = can_contiguously_add(numbers, target)
+    .ok_or_else(|| AocError::NoSolution(format!("no contiguous range sums to {}", target)))?;
+  add_smallest_largest(&numbers[start..end])
+}
+
+// The first number that isn't the sum of two of the `pre` numbers before it
+fn first_invalid(numbers: &[i64], pre: usize) -> Result<i64, AocError> {
   for i in pre..numbers.len() {
     let range_start = i - pre;
     let range_end = if pre + i > numbers.len() {
@@ -59,53 +77,114 @@ fn parse_part_1(input: &str, pre: usize) -> i64 {
     };
 
     if !can_add(&numbers[range_start..range_end], numbers[i]) {
-      return numbers[i];
+      return Ok(numbers[i]);
     }
   }
 
-  panic!("Not found");
+  Err(AocError::NoSolution(format!(
+    "every number is the sum of two of the {} before it",
+    pre
+  )))
 }
 
-pub fn part1(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(parse_part_1(input, 25) as i64)
+pub fn solve_part1(numbers: &[i64]) -> Result<i64, AocError> {
+  first_invalid(numbers, 25)
 }
 
-pub fn part2(input: &str) -> Result<i64, Box<dyn Error>> {
-  Ok(parse_part_2(input, part1(input).unwrap()) as i64)
+pub fn solve_part2(numbers: &[i64]) -> Result<i64, AocError> {
+  encryption_weakness(numbers, first_invalid(numbers, 25)?)
+}
+
+pub struct Day9;
+
+impl Day for Day9 {
+  type Model = Vec<i64>;
+  const SOURCE: &'static str = include_str!("day9.rs");
+
+  fn day(&self) -> u32 {
+    9
+  }
+
+  fn title(&self) -> &'static str {
+    "Encoding Error"
+  }
+
+  fn parse(&self, input: &Input) -> Result<Self::Model, AocError> {
+    parse_input(input)
+  }
+
+  fn part1(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part1(model)?.into())
+  }
+
+  fn part2(&self, model: &Self::Model) -> Result<Answer, AocError> {
+    Ok(solve_part2(model)?.into())
+  }
+
+  // A preamble of 25 and then `size` numbers (1 to 1000), each the sum of two of the
+  // 25 before it except the last, which is the sum of a contiguous range instead. The
+  // sums use the smallest numbers to keep them from overflowing, and the invalid one
+  // comes last because `first_invalid` also looks at the numbers after it.
+  fn generate(&self, rng: &mut Rng, params: &Params) -> Option<String> {
+    let size = params.size.clamp(1, 1000);
+    let mut numbers: Vec<i64> = (1..=50).collect();
+    rng.shuffle(&mut numbers);
+    numbers.truncate(25);
+    for _ in 1..size {
+      let mut window = numbers[numbers.len() - 25..].to_vec();
+      window.sort_unstable();
+      numbers.push(window[rng.range(0, 2) as usize] + window[rng.range(3, 7) as usize]);
+    }
+    let end = numbers.len();
+    let mut range = (rng.below(end as u64 - 1) as usize, 0);
+    range.1 = range.0 + 2;
+    // Longer ranges eventually sum to more than any two numbers
+    while can_add(&numbers[end - 25..], numbers[range.0..range.1].iter().sum()) {
+      if range.1 < end {
+        range.1 += 1;
+      } else {
+        range.0 -= 1;
+      }
+    }
+    let invalid: i64 = numbers[range.0..range.1].iter().sum();
+    numbers.push(invalid);
+    Some(generate::lines(&numbers))
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::examples::Example;
 
-  static INPUT_PART_1: &str = r"35
-20
-15
-25
-47
-40
-62
-55
-65
-95
-102
-117
-150
-182
-127
-219
-299
-277
-309
-576";
+  // The example uses a preamble of 5 rather than 25, so isn't checked through the registry
+  fn example() -> Vec<i64> {
+    parse(&Example::load(9, "1").unwrap().input).unwrap()
+  }
 
   #[test]
   fn test_part1() {
-    assert_eq!(127, parse_part_1(INPUT_PART_1, 5));
+    assert_eq!(127, first_invalid(&example(), 5).unwrap());
   }
 
   #[test]
   fn test_part2() {
-    assert_eq!(62, parse_part_2(INPUT_PART_1, 127))
+    assert_eq!(62, encryption_weakness(&example(), 127).unwrap())
+  }
+
+  #[test]
+  fn test_overflow() {
+    let mut numbers = vec![i64::MAX; 25];
+    numbers.push(5);
+    assert_eq!(Ok(5), solve_part1(&numbers));
+    assert_eq!(Ok(10), solve_part2(&numbers));
+    // The only range summing to i64::MAX is itself, which doubles past it
+    let mut numbers = vec![1; 25];
+    numbers.push(i64::MAX);
+    assert_eq!(Ok(i64::MAX), solve_part1(&numbers));
+    assert!(matches!(
+      solve_part2(&numbers),
+      Err(AocError::NoSolution(_))
+    ));
   }
 }
@@ -0,0 +1,277 @@
+// Differential testing: solves a part with each of its implementations, the day's own
+// `part1` / `part2` and any `Day::alternatives`, on the day's examples and on generated
+// inputs, and reports the first input where they disagree. Two implementations agree
+// when they give the same answer, or when both fail.
+
+use crate::examples;
+use crate::generate::Params;
+use crate::rng::Rng;
+use crate::{cancel, Answer, AocError, Solution};
+use std::sync::Arc;
+use std::time::Duration;
+
+// What the day's own solver is called in reports
+pub const REFERENCE: &str = "reference";
+
+// How to pick the inputs to compare on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Options {
+  // Generated inputs all come from one generator seeded with this
+  pub seed: u64,
+  // How many inputs to generate, for days that can generate them
+  pub cases: usize,
+  // How long each implementation may take on one input before that input is skipped
+  pub timeout: Duration,
+}
+
+impl Default for Options {
+  fn default() -> Options {
+    Options {
+      seed: 1,
+      cases: 100,
+      timeout: Duration::from_secs(1),
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Case {
+  // Where the input came from, e.g. "examples/day13/2.txt" or "generated #4"
+  pub label: String,
+  pub input: String,
+}
+
+// The inputs for `day`: its examples, then generated inputs of growing size
+pub fn cases(solution: &dyn Solution, options: &Options) -> Result<Vec<Case>, AocError> {
+  let day = solution.day();
+  let mut cases: Vec<Case> = examples::all()?
+    .into_iter()
+    .filter(|example| example.day == day)
+    .map(|example| Case {
+      label: format!("examples/day{}/{}.txt", day, example.name),
+      input: example.input,
+    })
+    .collect();
+  let mut rng = Rng::new(options.seed);
+  for i in 0..options.cases {
+    let size = 1 + i % 10;
+    let params = Params {
+      size,
+      ..Params::default()
+    };
+    match solution.generate(&mut rng, &params) {
+      Some(input) => cases.push(Case {
+        label: format!(
+          "generated #{} (seed {}, size {})",
+          i + 1,
+          options.seed,
+          size
+        ),
+        input,
+      }),
+      None => break,
+    }
+  }
+  Ok(cases)
+}
+
+// Each implementation's name and result on one input
+pub type Results = Vec<(&'static str, Result<Answer, AocError>)>;
+
+#[derive(Debug, PartialEq)]
+pub struct Report {
+  pub day: u32,
+  pub part: u32,
+  // The reference first, then the alternatives
+  pub implementations: Vec<&'static str>,
+  // Inputs every implementation finished on and agreed about
+  pub agreed: usize,
+  // Inputs that didn't parse, or that some implementation timed out on
+  pub skipped: usize,
+  // The first input they disagreed on, with each implementation's result
+  pub disagreement: Option<(Case, Results)>,
+}
+
+// Compares every implementation of `part` on `cases`, stopping at the first
+// disagreement
+pub fn compare(
+  solution: &'static dyn Solution,
+  part: u32,
+  cases: &[Case],
+  timeout: Duration,
+) -> Report {
+  let mut implementations = vec![REFERENCE];
+  implementations.extend(solution.alternatives(part));
+  let mut report = Report {
+    day: solution.day(),
+    part,
+    implementations: implementations.clone(),
+    agreed: 0,
+    skipped: 0,
+    disagreement: None,
+  };
+  for case in cases {
+    let input = case.input.clone();
+    let model = match cancel::with_timeout(timeout, move || {
+      solution.parse(&crate::input::Input::new(&input))
+    }) {
+      Ok(model) => Arc::new(model),
+      Err(_) => {
+        report.skipped += 1;
+        continue;
+      }
+    };
+    let results: Results = implementations
+      .iter()
+      .map(|&name| {
+        let model = Arc::clone(&model);
+        let result = cancel::with_timeout(timeout, move || match name {
+          REFERENCE => solution.solve_parsed(part, &model),
+          name => solution.solve_alternative(part, name, &model),
+        });
+        (name, result)
+      })
+      .collect();
+    if results
+      .iter()
+      .any(|(_, result)| matches!(result, Err(AocError::Timeout(_))))
+    {
+      report.skipped += 1;
+      continue;
+    }
+    let agree = results
+      .windows(2)
+      .all(|pair| match (&pair[0].1, &pair[1].1) {
+        (Ok(a), Ok(b)) => a.to_string() == b.to_string(),
+        (Err(_), Err(_)) => true,
+        _ => false,
+      });
+    if !agree {
+      report.disagreement = Some((case.clone(), results));
+      break;
+    }
+    report.agreed += 1;
+  }
+  report
+}
+
+// Every (day, part) with more than one implementation, in order
+pub fn parts() -> Vec<(u32, u32)> {
+  crate::solved()
+    .into_iter()
+    .filter(|&(day, part)| {
+      crate::find(day).is_some_and(|solution| !solution.alternatives(part).is_empty())
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::input::Input;
+  use crate::{Alternative, Day};
+
+  // A day whose alternative is wrong for any input with more than one line
+  struct Broken;
+
+  impl Day for Broken {
+    type Model = Vec<String>;
+    const SOURCE: &'static str = "";
+
+    fn day(&self) -> u32 {
+      99
+    }
+
+    fn title(&self) -> &'static str {
+      "Broken"
+    }
+
+    fn parse(&self, input: &Input) -> Result<Self::Model, AocError> {
+      Ok(input.lines().map(String::from).collect())
+    }
+
+    fn part1(&self, lines: &Self::Model) -> Result<Answer, AocError> {
+      Ok(lines.len().into())
+    }
+
+    fn alternatives(&self) -> Vec<Alternative<Self::Model>> {
+      vec![Alternative {
+        part: 1,
+        name: "first line only",
+        solve: |lines| Ok(lines.len().min(1).into()),
+      }]
+    }
+
+    fn generate(&self, rng: &mut Rng, params: &Params) -> Option<String> {
+      Some("x\n".repeat(rng.below(params.size as u64) as usize))
+    }
+  }
+
+  static BROKEN: Broken = Broken;
+
+  #[test]
+  fn test_finds_disagreement() {
+    let case = |input: &str| Case {
+      label: String::from(input),
+      input: String::from(input),
+    };
+    let report = compare(
+      &BROKEN,
+      1,
+      &[case(""), case("a"), case("a\nb"), case("a\nb\nc")],
+      Duration::from_secs(10),
+    );
+    assert_eq!(vec![REFERENCE, "first line only"], report.implementations);
+    assert_eq!((2, 0), (report.agreed, report.skipped));
+    let (case, results) = report.disagreement.unwrap();
+    assert_eq!("a\nb", case.input);
+    assert_eq!(
+      vec![
+        (REFERENCE, Ok(Answer::Int(2))),
+        ("first line only", Ok(Answer::Int(1)))
+      ],
+      results
+    );
+  }
+
+  #[test]
+  fn test_cases() {
+    let options = Options {
+      seed: 3,
+      cases: 5,
+      ..Options::default()
+    };
+    let cases = cases(&BROKEN, &options).unwrap();
+    assert_eq!(5, cases.len());
+    assert_eq!("generated #5 (seed 3, size 5)", cases[4].label);
+    assert_eq!(cases, super::cases(&BROKEN, &options).unwrap());
+
+    // Examples come first
+    let cases = super::cases(crate::find(13).unwrap(), &options).unwrap();
+    assert_eq!("examples/day13/1.txt", cases[0].label);
+    assert_eq!(6 + 5, cases.len());
+  }
+
+  // Every part with alternatives agrees with them on its examples and generated inputs.
+  // Inputs too big for a slow alternative are skipped, not failures.
+  #[test]
+  fn test_alternatives_agree() {
+    let options = Options {
+      seed: 2020,
+      cases: 30,
+      timeout: Duration::from_millis(200),
+    };
+    assert!(parts().contains(&(13, 2)));
+    for (day, part) in parts() {
+      let solution = crate::find(day).unwrap();
+      let report = compare(
+        solution,
+        part,
+        &cases(solution, &options).unwrap(),
+        options.timeout,
+      );
+      assert_eq!(None, report.disagreement, "day {} part {}", day, part);
+      assert!(report.agreed > 0, "day {} part {}", day, part);
+    }
+  }
+}
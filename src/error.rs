@@ -0,0 +1,188 @@
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AocError {
+  // `line` and `column` are 1-based, and `text` is the whole offending line
+  Parse {
+    line: usize,
+    column: usize,
+    text: String,
+    message: String,
+  },
+  NoSolution(String),
+  InvalidArgument(String),
+  DayNotFound(u32),
+  PartNotImplemented(u32, u32),
+  // The input couldn't be read, e.g. because the file doesn't exist
+  Io(String),
+  // The solver was cancelled after running for this long
+  Timeout(Duration),
+  // A request to the puzzle site failed
+  Http(String),
+}
+
+impl AocError {
+  // A parse error pointing at `token`, which should be a slice of `text`. Parsers that
+  // only see a single line leave the line number to `at_line`.
+  pub fn parse(text: &str, token: &str, message: impl Into<String>) -> AocError {
+    AocError::Parse {
+      line: 0,
+      column: column(text, token),
+      text: String::from(text),
+      message: message.into(),
+    }
+  }
+
+  // The process exit code the binary reports this error with, as listed in `cli::USAGE`
+  pub fn exit_code(&self) -> i32 {
+    match self {
+      AocError::InvalidArgument(_)
+      | AocError::DayNotFound(_)
+      | AocError::PartNotImplemented(..) => 2,
+      AocError::Io(_) => 3,
+      AocError::Parse { .. } => 4,
+      AocError::NoSolution(_) => 5,
+      AocError::Timeout(_) => 6,
+      AocError::Http(_) => 7,
+    }
+  }
+
+  // A stable name for the kind of error, for machine-readable output
+  pub fn kind(&self) -> &'static str {
+    match self {
+      AocError::Parse { .. } => "parse",
+      AocError::NoSolution(_) => "no_solution",
+      AocError::InvalidArgument(_) => "invalid_argument",
+      AocError::DayNotFound(_) => "day_not_found",
+      AocError::PartNotImplemented(..) => "part_not_implemented",
+      AocError::Io(_) => "io",
+      AocError::Timeout(_) => "timeout",
+      AocError::Http(_) => "http",
+    }
+  }
+
+  // An error in one of our own files at `path`, like aoc.toml or answers.toml. That's
+  // a bad argument rather than a bad puzzle input, so a syntax error there becomes an
+  // `InvalidArgument` naming the file, which exits with 2 rather than 4
+  pub fn in_file(self, path: &Path) -> AocError {
+    match self {
+      AocError::Parse {
+        line,
+        column,
+        message,
+        ..
+      } => AocError::InvalidArgument(format!(
+        "{}: line {}, column {}: {}",
+        path.display(),
+        line,
+        column,
+        message
+      )),
+      AocError::InvalidArgument(message) => {
+        AocError::InvalidArgument(format!("{}: {}", path.display(), message))
+      }
+      other => other,
+    }
+  }
+
+  pub fn at_line(self, line: usize) -> AocError {
+    match self {
+      AocError::Parse {
+        column,
+        text,
+        message,
+        ..
+      } => AocError::Parse {
+        line,
+        column,
+        text,
+        message,
+      },
+      other => other,
+    }
+  }
+}
+
+impl fmt::Display for AocError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      AocError::Parse {
+        line,
+        column,
+        text,
+        message,
+      } => {
+        writeln!(f, "line {}, column {}: {}", line, column, message)?;
+        writeln!(f, "  {}", text)?;
+        write!(f, "  {:>width$}", "^", width = column)
+      }
+      AocError::NoSolution(message) => write!(f, "no solution found: {}", message),
+      AocError::InvalidArgument(message) => write!(f, "invalid argument: {}", message),
+      AocError::DayNotFound(day) => write!(f, "day {} not found", day),
+      AocError::PartNotImplemented(day, part) => {
+        write!(f, "day {} part {} not implemented", day, part)
+      }
+      AocError::Io(message) => write!(f, "unable to read input: {}", message),
+      AocError::Timeout(limit) => write!(f, "timed out after {:?}", limit),
+      AocError::Http(message) => write!(f, "request failed: {}", message),
+    }
+  }
+}
+
+impl Error for AocError {}
+
+// 1-based character column of `token` within `text`, or 1 if it isn't a slice of it
+fn column(text: &str, token: &str) -> usize {
+  let offset = (token.as_ptr() as usize).wrapping_sub(text.as_ptr() as usize);
+  match text.get(..offset) {
+    Some(before) if offset + token.len() <= text.len() => before.chars().count() + 1,
+    _ => 1,
+  }
+}
+
+// Parses `token` (a slice of `text`) as a number, describing it as `what` on failure
+pub(crate) fn parse_number<T: FromStr>(text: &str, token: &str, what: &str) -> Result<T, AocError> {
+  token
+    .parse()
+    .map_err(|_| AocError::parse(text, token, format!("expected {}, found {:?}", what, token)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_column() {
+    let text = "1-3 a: abcde";
+    assert_eq!(1, column(text, &text[..1]));
+    assert_eq!(5, column(text, &text[4..5]));
+    assert_eq!(1, column(text, "elsewhere"));
+  }
+
+  #[test]
+  fn test_display() {
+    let err = AocError::parse("1-x a: abc", &"1-x a: abc"[2..3], "expected a number").at_line(4);
+    assert_eq!(
+      "line 4, column 3: expected a number\n  1-x a: abc\n    ^",
+      err.to_string()
+    );
+  }
+
+  #[test]
+  fn test_in_file() {
+    let err = AocError::parse("jobs = x", &"jobs = x"[7..], "expected a number")
+      .at_line(3)
+      .in_file(Path::new("aoc.toml"));
+    assert_eq!(2, err.exit_code());
+    assert_eq!(
+      "invalid argument: aoc.toml: line 3, column 8: expected a number",
+      err.to_string()
+    );
+    let err = AocError::Io(String::from("aoc.toml: denied"));
+    assert_eq!(3, err.in_file(Path::new("aoc.toml")).exit_code());
+  }
+}
@@ -0,0 +1,191 @@
+// The worked examples from the puzzle text, kept in `examples/dayN/*.txt`. Each file
+// starts with its expected answers, in the same format as answers.toml, and a `---`
+// line, and everything after that is the example input:
+//
+//   part1 = 35
+//   part2 = 8
+//   ---
+//   16
+//   10
+//
+// Parts without an answer aren't checked, e.g. for an example that only appears in
+// part 2 of the puzzle. One test runs every file through the registry, so adding a
+// case only means adding a file.
+
+use crate::config::ROOT;
+use crate::{kv, AocError};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, PartialEq)]
+pub struct Example {
+  pub day: u32,
+  // The file name without `.txt`
+  pub name: String,
+  // (part, answer), in the order they're given
+  pub answers: Vec<(u32, String)>,
+  pub input: String,
+}
+
+pub fn dir() -> PathBuf {
+  Path::new(ROOT).join("examples")
+}
+
+impl Example {
+  pub fn parse(day: u32, name: &str, text: &str) -> Result<Example, AocError> {
+    let split = match text.strip_prefix("---\n") {
+      Some(_) => Some(0),
+      None => text.find("\n---\n").map(|index| index + 1),
+    };
+    let (header, input) = match split {
+      Some(index) => (&text[..index], &text[index + 4..]),
+      None => {
+        return Err(AocError::InvalidArgument(String::from(
+          "expected the answers to end with a \"---\" line",
+        )))
+      }
+    };
+    let mut answers: Vec<(u32, String)> = Vec::new();
+    for entry in kv::parse(header)? {
+      let part = match entry.key.strip_prefix("part").map(str::parse) {
+        Some(Ok(part)) if entry.section.is_empty() => part,
+        _ => {
+          return Err(AocError::InvalidArgument(format!(
+            "expected a partN key on line {}",
+            entry.line
+          )))
+        }
+      };
+      if answers.iter().any(|&(seen, _)| seen == part) {
+        return Err(AocError::InvalidArgument(format!(
+          "duplicate answer for part {} on line {}",
+          part, entry.line
+        )));
+      }
+      answers.push((part, entry.value));
+    }
+    Ok(Example {
+      day,
+      name: String::from(name),
+      answers,
+      input: String::from(input),
+    })
+  }
+
+  // The example in `examples/day{day}/{name}.txt`
+  pub fn load(day: u32, name: &str) -> Result<Example, AocError> {
+    let path = dir()
+      .join(format!("day{}", day))
+      .join(format!("{}.txt", name));
+    let text =
+      fs::read_to_string(&path).map_err(|e| AocError::Io(format!("{}: {}", path.display(), e)))?;
+    Example::parse(day, name, &text)
+      .map_err(|e| AocError::InvalidArgument(format!("{}: {}", path.display(), e)))
+  }
+}
+
+// Every example under `examples`, by day and then by name
+pub fn all() -> Result<Vec<Example>, AocError> {
+  let mut found: Vec<(u32, String)> = Vec::new();
+  for entry in read_dir(&dir())? {
+    let day = entry
+      .to_str()
+      .and_then(|name| name.strip_prefix("day")?.parse().ok());
+    let day = day.ok_or_else(|| {
+      AocError::InvalidArgument(format!(
+        "expected only dayN directories in examples, found {:?}",
+        entry
+      ))
+    })?;
+    for file in read_dir(&dir().join(&entry))? {
+      match file.to_str().and_then(|name| name.strip_suffix(".txt")) {
+        Some(name) => found.push((day, String::from(name))),
+        None => {
+          return Err(AocError::InvalidArgument(format!(
+            "expected only .txt files in examples/day{}, found {:?}",
+            day, file
+          )))
+        }
+      }
+    }
+  }
+  // Numbered examples in numeric order
+  found.sort_by(|(a, x), (b, y)| (a, x.len(), x).cmp(&(b, y.len(), y)));
+  found
+    .iter()
+    .map(|(day, name)| Example::load(*day, name))
+    .collect()
+}
+
+fn read_dir(path: &Path) -> Result<Vec<std::ffi::OsString>, AocError> {
+  let io = |e: std::io::Error| AocError::Io(format!("{}: {}", path.display(), e));
+  fs::read_dir(path)
+    .map_err(io)?
+    .map(|entry| Ok(entry.map_err(io)?.file_name()))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::answers::{self, Verdict};
+
+  #[test]
+  fn test_parse() {
+    let example = Example::parse(
+      10,
+      "1",
+      "part1 = 35\n# a comment\npart2 = \"8\"\n---\n16\n10\n",
+    )
+    .unwrap();
+    assert_eq!(
+      Example {
+        day: 10,
+        name: String::from("1"),
+        answers: vec![(1, String::from("35")), (2, String::from("8"))],
+        input: String::from("16\n10\n"),
+      },
+      example
+    );
+
+    // Input lines that look like a header are left alone
+    let example = Example::parse(11, "1", "---\n#.\n---\n").unwrap();
+    assert!(example.answers.is_empty());
+    assert_eq!("#.\n---\n", example.input);
+
+    for text in &[
+      "part1 = 35\n16\n",
+      "part1 = 35\n--- \n16\n",
+      "part1 = 35 ---\n16\n",
+      "answer = 35\n---\n",
+      "[day10]\npart1 = 35\n---\n",
+      "part1 = 35\npart1 = 36\n---\n",
+    ] {
+      assert!(Example::parse(10, "1", text).is_err(), "{:?}", text);
+    }
+  }
+
+  // Every example in the corpus gives its expected answers through the registry
+  #[test]
+  fn test_examples() {
+    let examples = all().unwrap();
+    assert!(!examples.is_empty());
+    let mut failures = Vec::new();
+    for example in &examples {
+      let parsed = crate::parse(example.day, &example.input);
+      for (part, expected) in &example.answers {
+        let actual = match &parsed {
+          Ok(parsed) => parsed.solve(*part),
+          Err(err) => Err(err.clone()),
+        };
+        if answers::check(Some(expected), &actual) != Verdict::Pass {
+          failures.push(format!(
+            "examples/day{}/{}.txt part {}: expected {}, found {:?}",
+            example.day, example.name, part, expected, actual
+          ));
+        }
+      }
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+  }
+}
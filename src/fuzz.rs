@@ -0,0 +1,245 @@
+// Fuzzing every day: mangled inputs go through `crate::parse` and then both parts of
+// whatever parses, which must return an error for anything they can't handle rather
+// than panic. Each day's inputs
+// are mutations of a seed corpus: its worked examples, a generated input, and the files
+// in `fuzz/dayN/`, which hold inputs that once crashed the parser so that they stay
+// fixed. Mutations come from a seeded `Rng`, so every run tries the same inputs.
+
+use crate::config::ROOT;
+use crate::generate::{self, Params};
+use crate::rng::Rng;
+use crate::{examples, AocError, Solution};
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// How many mutations of each seed input are tried
+pub const MUTATIONS: usize = 500;
+
+// How long each part gets on an input before giving up with a `Timeout`, which is as
+// good as any other error here. Mutations can set day 13 searching for hours
+const SOLVE_TIMEOUT: Duration = Duration::from_millis(20);
+
+// Characters that parsers tend to trip over, mixed in with ones from the input
+const NASTY: &[char] = &[
+  '0', '1', '9', '-', '+', ' ', '\n', '\t', '\r', ':', '=', '[', ']', ',', '.', '#', 'x', 'X',
+  '\0', 'é', '€', '😀',
+];
+
+// Numbers at the edges of the types parsers read into
+const NUMBERS: &[&str] = &[
+  "0",
+  "-0",
+  "-1",
+  "255",
+  "4294967296",
+  "9223372036854775807",
+  "-9223372036854775807",
+  "-9223372036854775808",
+  "18446744073709551615",
+  "99999999999999999999",
+];
+
+pub fn dir() -> PathBuf {
+  Path::new(ROOT).join("fuzz")
+}
+
+// The inputs that `day`'s mutations start from
+pub fn corpus(solution: &dyn Solution) -> Result<Vec<String>, AocError> {
+  let day = solution.day();
+  let mut corpus: Vec<String> = examples::all()?
+    .into_iter()
+    .filter(|example| example.day == day)
+    .map(|example| example.input)
+    .collect();
+  let options = generate::Options {
+    seed: 1,
+    params: Params {
+      size: 20,
+      ..Params::default()
+    },
+  };
+  if let Ok(input) = generate::generate(solution, &options) {
+    corpus.push(input);
+  }
+  let path = dir().join(format!("day{}", day));
+  if path.is_dir() {
+    let io = |e: std::io::Error| AocError::Io(format!("{}: {}", path.display(), e));
+    let mut files: Vec<PathBuf> = fs::read_dir(&path)
+      .map_err(io)?
+      .map(|entry| Ok(entry.map_err(io)?.path()))
+      .collect::<Result<_, AocError>>()?;
+    files.sort();
+    for file in files {
+      corpus.push(fs::read_to_string(&file).map_err(io)?);
+    }
+  }
+  Ok(corpus)
+}
+
+// `input` with one to four random edits
+pub fn mutate(rng: &mut Rng, input: &str) -> String {
+  let mut chars: Vec<char> = input.chars().collect();
+  for _ in 0..rng.range(1, 4) {
+    let len = chars.len() as u64;
+    let at = rng.below(len + 1) as usize;
+    let end = (at + rng.below(8) as usize + 1).min(chars.len());
+    match rng.below(8) {
+      // Replace a character
+      0 if at < chars.len() => chars[at] = nasty(rng, &chars),
+      // Insert a few characters
+      1 => {
+        for _ in 0..rng.range(1, 3) {
+          let c = nasty(rng, &chars);
+          chars.insert(at, c);
+        }
+      }
+      // Delete a few characters
+      2 => {
+        chars.drain(at..end);
+      }
+      // Repeat a few characters
+      3 => {
+        let copy: Vec<char> = chars[at..end].to_vec();
+        chars.splice(at..at, copy);
+      }
+      // Insert an extreme number
+      4 => {
+        chars.splice(at..at, rng.pick(NUMBERS).chars());
+      }
+      // Cut the input short
+      5 => chars.truncate(at),
+      // Swap a number for an extreme one
+      6 => {
+        let digits: Vec<usize> = (0..chars.len())
+          .filter(|&i| chars[i].is_ascii_digit())
+          .collect();
+        if !digits.is_empty() {
+          let start = *rng.pick(&digits);
+          let mut stop = start;
+          while stop < chars.len() && chars[stop].is_ascii_digit() {
+            stop += 1;
+          }
+          chars.splice(start..stop, rng.pick(NUMBERS).chars());
+        }
+      }
+      // Duplicate or drop a whole line
+      _ => {
+        let text: String = chars.iter().collect();
+        let mut lines: Vec<&str> = text.split('\n').collect();
+        let line = rng.below(lines.len() as u64) as usize;
+        match rng.one_in(2) {
+          true => lines.insert(line, lines[line]),
+          false => {
+            lines.remove(line);
+          }
+        }
+        chars = lines.join("\n").chars().collect();
+      }
+    }
+  }
+  chars.into_iter().collect()
+}
+
+fn nasty(rng: &mut Rng, chars: &[char]) -> char {
+  match chars.is_empty() || rng.one_in(2) {
+    true => *rng.pick(NASTY),
+    false => *rng.pick(chars),
+  }
+}
+
+// Parses `input` as `day` and solves both parts if it parses, returning the panic
+// message if the parser or a part panicked
+pub fn check(day: u32, input: &str) -> Result<(), String> {
+  let run = || {
+    if let Ok(parsed) = crate::parse(day, input) {
+      for part in 1..=2 {
+        let _ = parsed.solve_with_timeout(part, Some(SOLVE_TIMEOUT));
+      }
+    }
+  };
+  match panic::catch_unwind(AssertUnwindSafe(run)) {
+    Ok(()) => Ok(()),
+    Err(payload) => Err(
+      payload
+        .downcast_ref::<String>()
+        .cloned()
+        .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+        .unwrap_or_else(|| String::from("a panic without a message")),
+    ),
+  }
+}
+
+// The shortest input found, by dropping lines and then characters, that still panics
+pub fn minimize(day: u32, input: &str) -> String {
+  let mut input = String::from(input);
+  for &by_line in &[true, false] {
+    let mut i = 0;
+    loop {
+      let mut pieces: Vec<&str> = match by_line {
+        true => input.split_inclusive('\n').collect(),
+        false => input
+          .char_indices()
+          .map(|(index, c)| &input[index..index + c.len_utf8()])
+          .collect(),
+      };
+      if i >= pieces.len() {
+        break;
+      }
+      pieces.remove(i);
+      let candidate = pieces.concat();
+      match check(day, &candidate) {
+        Err(_) => input = candidate,
+        Ok(()) => i += 1,
+      }
+    }
+  }
+  input
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_mutate() {
+    let (mut a, mut b) = (Rng::new(3), Rng::new(3));
+    for _ in 0..100 {
+      assert_eq!(
+        mutate(&mut a, "nop +0\nacc +1"),
+        mutate(&mut b, "nop +0\nacc +1")
+      );
+    }
+    assert!((0..100).any(|_| mutate(&mut a, "nop +0") != "nop +0"));
+  }
+
+  // No day's parser or parts panic on its corpus or any mutation of it
+  #[test]
+  fn test_days_never_panic() {
+    let mut failures = Vec::new();
+    for &solution in crate::solutions() {
+      let day = solution.day();
+      let mut rng = Rng::new(day as u64);
+      let mut inputs = corpus(solution).unwrap();
+      assert!(!inputs.is_empty(), "day {} has no fuzz corpus", day);
+      let seeds = inputs.clone();
+      for seed in &seeds {
+        inputs.extend((0..MUTATIONS).map(|_| mutate(&mut rng, seed)));
+      }
+      if let Some((input, message)) = inputs
+        .iter()
+        .find_map(|input| check(day, input).err().map(|message| (input, message)))
+      {
+        failures.push(format!(
+          "day {} panicked: {}\n  minimal input: {:?}\n  found with: {:?}",
+          day,
+          message,
+          minimize(day, input),
+          input
+        ));
+      }
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+  }
+}
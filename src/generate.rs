@@ -0,0 +1,128 @@
+// `gen <day>`: random puzzle inputs in the format each day's parser expects, for stress
+// tests and benchmarks with inputs bigger or stranger than a personal one. Each day
+// makes its own with `Day::generate`. The same seed always gives the same input, and
+// every input has a solution.
+
+use crate::rng::Rng;
+use crate::{AocError, Solution};
+
+// What to generate, past the seed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Params {
+  // Roughly how many items (lines, passports, bags...) the input has. Days clamp it to
+  // what their puzzle allows
+  pub size: usize,
+  // The percentage of items that should fail validation, for days that count the
+  // valid ones
+  pub invalid: f64,
+}
+
+impl Default for Params {
+  fn default() -> Params {
+    Params {
+      size: 100,
+      invalid: 25.0,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Options {
+  pub seed: u64,
+  pub params: Params,
+}
+
+impl Default for Options {
+  fn default() -> Options {
+    Options {
+      seed: 1,
+      params: Params::default(),
+    }
+  }
+}
+
+pub fn generate(solution: &dyn Solution, options: &Options) -> Result<String, AocError> {
+  solution
+    .generate(&mut Rng::new(options.seed), &options.params)
+    .ok_or_else(|| {
+      AocError::InvalidArgument(format!("day {} has no input generator", solution.day()))
+    })
+}
+
+// How many of `size` items should be invalid
+pub fn invalid_count(params: &Params) -> usize {
+  (params.size as f64 * params.invalid.min(100.0) / 100.0).round() as usize
+}
+
+// One item per line
+pub fn lines<T: ToString>(items: &[T]) -> String {
+  items.iter().map(|item| item.to_string() + "\n").collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::cancel;
+  use crate::input::Input;
+  use std::time::Duration;
+
+  #[test]
+  fn test_seeded() {
+    let day = crate::find(7).unwrap();
+    let options = Options::default();
+    assert_eq!(
+      generate(day, &options).unwrap(),
+      generate(day, &options).unwrap()
+    );
+    let other = Options { seed: 2, ..options };
+    assert_ne!(
+      generate(day, &options).unwrap(),
+      generate(day, &other).unwrap()
+    );
+    assert_eq!(
+      6,
+      invalid_count(&Params {
+        size: 25,
+        ..Params::default()
+      })
+    );
+  }
+
+  // Every day's inputs parse and solve, whatever the seed and size. Days without a
+  // generator yet, like a freshly scaffolded one, are skipped
+  #[test]
+  fn test_every_day_has_a_solution() {
+    for &solution in crate::solutions() {
+      let day = solution.day();
+      for &(seed, size) in &[(1, 0), (2, 1), (3, 2), (4, 10), (5, 40)] {
+        let options = Options {
+          seed,
+          params: Params {
+            size,
+            ..Params::default()
+          },
+        };
+        let input = match generate(solution, &options) {
+          Ok(input) => input,
+          Err(_) => break,
+        };
+        for &part in solution.parts() {
+          let input = input.clone();
+          let result = cancel::with_timeout(Duration::from_secs(10), move || {
+            solution.solve(part, &Input::new(&input))
+          });
+          assert!(
+            result.is_ok(),
+            "day {} part {} (seed {}, size {}): {:?}\n{}",
+            day,
+            part,
+            seed,
+            size,
+            result,
+            generate(solution, &options).unwrap()
+          );
+        }
+      }
+    }
+  }
+}
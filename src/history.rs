@@ -0,0 +1,206 @@
+// Every answer `submit` has had a verdict on, kept in submissions.toml so that a
+// known-wrong answer is never sent again:
+//
+//   [day1.part2]
+//   "162292409" = "too low"
+//   "162292410" = "right"
+
+use crate::config::ROOT;
+use crate::site::Outcome;
+use crate::{kv, AocError};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default)]
+pub struct History {
+  // In the order they were submitted
+  submissions: Vec<Submission>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Submission {
+  pub day: u32,
+  pub part: u32,
+  pub answer: String,
+  pub outcome: Outcome,
+}
+
+impl History {
+  pub fn default_path() -> PathBuf {
+    Path::new(ROOT).join("submissions.toml")
+  }
+
+  // A missing file is an empty history
+  pub fn load(path: &Path) -> Result<History, AocError> {
+    if !path.exists() {
+      return Ok(History::default());
+    }
+    let text =
+      fs::read_to_string(path).map_err(|e| AocError::Io(format!("{}: {}", path.display(), e)))?;
+    History::parse(&text).map_err(|err| err.in_file(path))
+  }
+
+  pub fn parse(text: &str) -> Result<History, AocError> {
+    let mut submissions = Vec::new();
+    for entry in kv::parse(text)? {
+      let mut section = entry.section.split('.');
+      let day = section
+        .next()
+        .and_then(|s| s.strip_prefix("day")?.parse().ok());
+      let part = section
+        .next()
+        .and_then(|s| s.strip_prefix("part")?.parse().ok());
+      let outcome = Outcome::from_label(&entry.value);
+      match (day, part, outcome) {
+        (Some(day), Some(part), Some(outcome)) => submissions.push(Submission {
+          day,
+          part,
+          answer: entry.key,
+          outcome,
+        }),
+        _ => {
+          return Err(AocError::InvalidArgument(format!(
+            "expected \"answer\" = \"right\", \"wrong\", \"too high\" or \"too low\" in a \
+             [dayN.partN] section on line {}",
+            entry.line
+          )))
+        }
+      }
+    }
+    Ok(History { submissions })
+  }
+
+  pub fn save(&self, path: &Path) -> Result<(), AocError> {
+    fs::write(path, self.to_text()).map_err(|e| AocError::Io(format!("{}: {}", path.display(), e)))
+  }
+
+  fn to_text(&self) -> String {
+    let mut text = String::from("# Answers sent by `submit`, and what the site said about them\n");
+    let mut sections: Vec<(u32, u32)> = self.submissions.iter().map(|s| (s.day, s.part)).collect();
+    sections.sort_unstable();
+    sections.dedup();
+    for (day, part) in sections {
+      text.push_str(&format!("\n[day{}.part{}]\n", day, part));
+      for submission in self.for_part(day, part) {
+        let label = submission.outcome.label().unwrap_or("wrong");
+        text.push_str(&format!(
+          "{} = {}\n",
+          kv::quote(&submission.answer),
+          kv::quote(label)
+        ));
+      }
+    }
+    text
+  }
+
+  fn for_part(&self, day: u32, part: u32) -> impl Iterator<Item = &Submission> {
+    self
+      .submissions
+      .iter()
+      .filter(move |s| s.day == day && s.part == part)
+  }
+
+  // Remembers the site's verdict on an answer. Outcomes that say nothing about the
+  // answer, like being rate limited, aren't kept.
+  pub fn record(&mut self, day: u32, part: u32, answer: &str, outcome: Outcome) {
+    if outcome.label().is_none() {
+      return;
+    }
+    self
+      .submissions
+      .retain(|s| !(s.day == day && s.part == part && s.answer == answer));
+    self.submissions.push(Submission {
+      day,
+      part,
+      answer: String::from(answer),
+      outcome,
+    });
+  }
+
+  // Why `answer` shouldn't be submitted, if the history already says how it would go:
+  // it was submitted before, the part is already solved, or it's beyond an answer that
+  // was too high or too low
+  pub fn verdict(&self, day: u32, part: u32, answer: &str) -> Option<String> {
+    let number: Option<i128> = answer.parse().ok();
+    for submission in self.for_part(day, part) {
+      let previous: Option<i128> = submission.answer.parse().ok();
+      let reason = match (&submission.outcome, number, previous) {
+        (Outcome::Right, _, _) if submission.answer == answer => {
+          Some(String::from("it was already accepted"))
+        }
+        (Outcome::Right, _, _) => Some(format!(
+          "the part was already solved with {}",
+          submission.answer
+        )),
+        (outcome, _, _) if submission.answer == answer => Some(format!(
+          "it was already rejected as {}",
+          outcome.label().unwrap_or("wrong")
+        )),
+        (Outcome::TooHigh, Some(n), Some(p)) if n >= p => {
+          Some(format!("{} was already too high", submission.answer))
+        }
+        (Outcome::TooLow, Some(n), Some(p)) if n <= p => {
+          Some(format!("{} was already too low", submission.answer))
+        }
+        _ => None,
+      };
+      if reason.is_some() {
+        return reason;
+      }
+    }
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_round_trip() {
+    let mut history = History::default();
+    history.record(1, 2, "162292409", Outcome::TooLow);
+    history.record(1, 1, "x = \"y\"", Outcome::Wrong);
+    history.record(1, 2, "162292410", Outcome::Right);
+    history.record(1, 2, "162292411", Outcome::RateLimited(None));
+    let text = history.to_text();
+    assert!(
+      text.contains("\n[day1.part2]\n\"162292409\" = \"too low\"\n\"162292410\" = \"right\"\n")
+    );
+    assert_eq!(text, History::parse(&text).unwrap().to_text());
+
+    assert!(History::parse("[day1]\n\"5\" = \"right\"").is_err());
+    assert!(History::parse("[day1.part1]\n\"5\" = \"maybe\"").is_err());
+  }
+
+  #[test]
+  fn test_verdict() {
+    let mut history = History::default();
+    history.record(7, 1, "300", Outcome::TooHigh);
+    history.record(7, 1, "100", Outcome::TooLow);
+    history.record(7, 1, "abc", Outcome::Wrong);
+
+    assert_eq!(None, history.verdict(7, 1, "250"));
+    assert_eq!(None, history.verdict(7, 2, "300"));
+    assert!(history
+      .verdict(7, 1, "300")
+      .unwrap()
+      .contains("already rejected"));
+    assert!(history.verdict(7, 1, "301").unwrap().contains("too high"));
+    assert!(history.verdict(7, 1, "99").unwrap().contains("too low"));
+    assert!(history
+      .verdict(7, 1, "abc")
+      .unwrap()
+      .contains("already rejected"));
+
+    history.record(7, 1, "250", Outcome::Right);
+    assert!(history
+      .verdict(7, 1, "250")
+      .unwrap()
+      .contains("already accepted"));
+    assert!(history
+      .verdict(7, 1, "251")
+      .unwrap()
+      .contains("already solved"));
+  }
+}
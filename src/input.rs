@@ -0,0 +1,207 @@
+use crate::AocError;
+
+// A puzzle input with the differences between editors and platforms ironed out: no
+// byte order mark, `\n` line endings only, and no trailing blank lines. Solvers should
+// read their input through this rather than splitting the raw text themselves.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Input {
+  text: String,
+}
+
+// A blank-line separated group of lines, e.g. one passport in day 4
+#[derive(Debug, PartialEq)]
+pub struct Paragraph<'a> {
+  // 1-based line number of the first line
+  pub line: usize,
+  pub lines: Vec<&'a str>,
+}
+
+impl Paragraph<'_> {
+  // Parses every line of the paragraph with `parse`, tagging errors with their line number
+  pub fn parse_lines<T, F>(&self, mut parse: F) -> Result<Vec<T>, AocError>
+  where
+    F: FnMut(&str) -> Result<T, AocError>,
+  {
+    self
+      .lines
+      .iter()
+      .enumerate()
+      .map(|(i, line)| parse(line).map_err(|err| err.at_line(self.line + i)))
+      .collect()
+  }
+}
+
+impl Input {
+  pub fn new(raw: &str) -> Input {
+    let text = raw.strip_prefix('\u{feff}').unwrap_or(raw);
+    let mut text = text.replace("\r\n", "\n");
+    let len = text.trim_end_matches('\n').len();
+    text.truncate(len);
+    Input { text }
+  }
+
+  pub fn as_str(&self) -> &str {
+    &self.text
+  }
+
+  // A stable 64-bit FNV-1a hash of the normalised text, as 16 hex digits, so that the
+  // same puzzle saved with different line endings hashes the same
+  pub fn hash(&self) -> String {
+    let hash = self
+      .text
+      .bytes()
+      .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+      });
+    format!("{:016x}", hash)
+  }
+
+  pub fn lines(&self) -> impl Iterator<Item = &str> {
+    self.text.lines()
+  }
+
+  // Parses every line with `parse`, tagging errors with their line number
+  pub fn parse_lines<'a, T, F>(&'a self, mut parse: F) -> Result<Vec<T>, AocError>
+  where
+    F: FnMut(&'a str) -> Result<T, AocError>,
+  {
+    self
+      .lines()
+      .enumerate()
+      .map(|(i, line)| parse(line).map_err(|err| err.at_line(i + 1)))
+      .collect()
+  }
+
+  // Groups of lines separated by one or more blank lines
+  pub fn paragraphs(&self) -> Vec<Paragraph<'_>> {
+    let mut paragraphs = Vec::new();
+    let mut current: Option<Paragraph> = None;
+    for (i, line) in self.lines().enumerate() {
+      if line.is_empty() {
+        paragraphs.extend(current.take());
+        continue;
+      }
+      current
+        .get_or_insert_with(|| Paragraph {
+          line: i + 1,
+          lines: Vec::new(),
+        })
+        .lines
+        .push(line);
+    }
+    paragraphs.extend(current);
+    paragraphs
+  }
+
+  // A non-empty rectangle of characters, each of which must be one of `cells`
+  pub fn grid(&self, cells: &str) -> Result<Vec<Vec<char>>, AocError> {
+    let mut grid: Vec<Vec<char>> = Vec::new();
+    for (i, line) in self.lines().enumerate() {
+      let mut row = Vec::new();
+      for (index, c) in line.char_indices() {
+        if !cells.contains(c) {
+          let token = &line[index..index + c.len_utf8()];
+          let expected: Vec<String> = cells.chars().map(|c| format!("'{}'", c)).collect();
+          let message = format!("expected one of {}", expected.join(", "));
+          return Err(AocError::parse(line, token, message).at_line(i + 1));
+        }
+        row.push(c);
+      }
+      if let Some(first) = grid.first() {
+        if row.len() != first.len() {
+          let token = &line[line.len().min(first.len())..];
+          let message = format!("expected {} columns, found {}", first.len(), row.len());
+          return Err(AocError::parse(line, token, message).at_line(i + 1));
+        }
+      }
+      grid.push(row);
+    }
+    match grid.first() {
+      Some(row) if !row.is_empty() => Ok(grid),
+      _ => Err(AocError::parse("", "", "expected a grid").at_line(1)),
+    }
+  }
+}
+
+impl From<&str> for Input {
+  fn from(raw: &str) -> Input {
+    Input::new(raw)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::error::parse_number;
+
+  #[test]
+  fn test_normalises() {
+    let input = Input::new("\u{feff}a b\r\n\r\nc\r\n\r\n\n");
+    assert_eq!("a b\n\nc", input.as_str());
+    assert_eq!(vec!["a b", "", "c"], input.lines().collect::<Vec<&str>>());
+  }
+
+  #[test]
+  fn test_hash() {
+    assert_eq!("cbf29ce484222325", Input::new("").hash());
+    assert_eq!("af63dc4c8601ec8c", Input::new("a\n").hash());
+    assert_eq!(Input::new("a\nb").hash(), Input::new("a\r\nb\r\n").hash());
+  }
+
+  #[test]
+  fn test_parse_lines() {
+    let input = Input::new("1\n2\nx\n4");
+    let err = input.parse_lines(|line| parse_number::<i64>(line, line, "a number"));
+    assert_eq!(
+      Err(AocError::Parse {
+        line: 3,
+        column: 1,
+        text: String::from("x"),
+        message: String::from("expected a number, found \"x\""),
+      }),
+      err
+    );
+  }
+
+  #[test]
+  fn test_paragraphs() {
+    let input = Input::new("a\nb\n\n\nc\r\n\r\nd\ne\n");
+    assert_eq!(
+      vec![
+        Paragraph {
+          line: 1,
+          lines: vec!["a", "b"]
+        },
+        Paragraph {
+          line: 5,
+          lines: vec!["c"]
+        },
+        Paragraph {
+          line: 7,
+          lines: vec!["d", "e"]
+        },
+      ],
+      input.paragraphs()
+    );
+  }
+
+  #[test]
+  fn test_grid() {
+    let grid = Input::new(".#\r\n#.\r\n").grid(".#").unwrap();
+    assert_eq!(vec![vec!['.', '#'], vec!['#', '.']], grid);
+
+    for (text, line, column) in &[
+      ("..\n.x", 2, 2),
+      ("..\n...", 2, 3),
+      ("..\n.", 2, 2),
+      ("", 1, 1),
+    ] {
+      match Input::new(text).grid(".#") {
+        Err(AocError::Parse {
+          line: l, column: c, ..
+        }) => assert_eq!((*line, *column), (l, c), "{:?}", text),
+        other => panic!("expected {:?} to be rejected, got {:?}", text, other),
+      }
+    }
+  }
+}
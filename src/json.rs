@@ -0,0 +1,163 @@
+// Just enough JSON to write the records of `--format json`. Objects keep their fields in
+// the order they were added so output is stable for diffing.
+
+use crate::AocError;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+  Null,
+  Bool(bool),
+  Int(i128),
+  Str(String),
+  Object(Vec<(&'static str, Value)>),
+}
+
+impl Value {
+  pub fn object() -> Value {
+    Value::Object(Vec::new())
+  }
+
+  // Adds a field to an object; does nothing to other values
+  pub fn with(mut self, key: &'static str, value: impl Into<Value>) -> Value {
+    if let Value::Object(fields) = &mut self {
+      fields.push((key, value.into()));
+    }
+    self
+  }
+}
+
+impl From<bool> for Value {
+  fn from(b: bool) -> Value {
+    Value::Bool(b)
+  }
+}
+
+impl From<&str> for Value {
+  fn from(s: &str) -> Value {
+    Value::Str(String::from(s))
+  }
+}
+
+impl From<String> for Value {
+  fn from(s: String) -> Value {
+    Value::Str(s)
+  }
+}
+
+macro_rules! from_int {
+  ($($t:ty),*) => {
+    $(impl From<$t> for Value {
+      fn from(n: $t) -> Value {
+        Value::Int(n as i128)
+      }
+    })*
+  };
+}
+
+from_int!(i32, i64, u32, u64, usize);
+
+impl From<u128> for Value {
+  fn from(n: u128) -> Value {
+    // Far beyond any duration or answer we'll see
+    Value::Int(n.min(i128::MAX as u128) as i128)
+  }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+  fn from(value: Option<T>) -> Value {
+    value.map_or(Value::Null, Into::into)
+  }
+}
+
+impl From<&AocError> for Value {
+  fn from(err: &AocError) -> Value {
+    let value = Value::object()
+      .with("kind", err.kind())
+      .with("exit_code", err.exit_code());
+    match err {
+      AocError::Parse {
+        line,
+        column,
+        text,
+        message,
+      } => value
+        .with("message", message.as_str())
+        .with("line", *line)
+        .with("column", *column)
+        .with("text", text.as_str()),
+      _ => value.with("message", err.to_string()),
+    }
+  }
+}
+
+impl fmt::Display for Value {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Value::Null => f.write_str("null"),
+      Value::Bool(b) => write!(f, "{}", b),
+      Value::Int(n) => write!(f, "{}", n),
+      Value::Str(s) => write_str(f, s),
+      Value::Object(fields) => {
+        f.write_str("{")?;
+        for (i, (key, value)) in fields.iter().enumerate() {
+          if i > 0 {
+            f.write_str(",")?;
+          }
+          write_str(f, key)?;
+          write!(f, ":{}", value)?;
+        }
+        f.write_str("}")
+      }
+    }
+  }
+}
+
+fn write_str(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+  f.write_str("\"")?;
+  for c in s.chars() {
+    match c {
+      '"' => f.write_str("\\\"")?,
+      '\\' => f.write_str("\\\\")?,
+      '\n' => f.write_str("\\n")?,
+      '\r' => f.write_str("\\r")?,
+      '\t' => f.write_str("\\t")?,
+      c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+      c => write!(f, "{}", c)?,
+    }
+  }
+  f.write_str("\"")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_display() {
+    let value = Value::object()
+      .with("day", 1u32)
+      .with("cached", false)
+      .with("answer", "a \"b\"\n\\c\u{1}")
+      .with("error", None::<String>)
+      .with("timings", Value::object().with("total_ns", 51_000u128));
+    assert_eq!(
+      r#"{"day":1,"cached":false,"answer":"a \"b\"\n\\c\u0001","error":null,"timings":{"total_ns":51000}}"#,
+      value.to_string()
+    );
+    assert_eq!("{}", Value::object().to_string());
+  }
+
+  #[test]
+  fn test_error() {
+    let err = AocError::parse("1-x a: abc", &"1-x a: abc"[2..3], "expected a number").at_line(4);
+    assert_eq!(
+      r#"{"kind":"parse","exit_code":4,"message":"expected a number","line":4,"column":3,"text":"1-x a: abc"}"#,
+      Value::from(&err).to_string()
+    );
+    assert_eq!(
+      r#"{"kind":"day_not_found","exit_code":2,"message":"day 25 not found"}"#,
+      Value::from(&AocError::DayNotFound(25)).to_string()
+    );
+  }
+}
@@ -0,0 +1,182 @@
+// A reader for the small subset of TOML used by our own files (the config file, known
+// answers, benchmark baselines and submission history): `# comments`, `[section]`
+// headers and `key = value` pairs, where keys are bare or quoted and values are
+// integers or double-quoted strings with \\, \", \n and \t escapes.
+
+use crate::AocError;
+
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+  // Empty for keys before the first section header
+  pub section: String,
+  pub key: String,
+  pub value: String,
+  pub line: usize,
+}
+
+pub fn parse(text: &str) -> Result<Vec<Entry>, AocError> {
+  let mut entries = Vec::new();
+  let mut section = String::new();
+
+  for (i, raw) in text.lines().enumerate() {
+    let line = raw.trim();
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+    if let Some(header) = line.strip_prefix('[') {
+      match header.strip_suffix(']') {
+        Some(name) if !name.trim().is_empty() => section = String::from(name.trim()),
+        _ => return Err(AocError::parse(raw, line, "expected a [section] header").at_line(i + 1)),
+      }
+      continue;
+    }
+    let (key, value) = match key_end(line).and_then(|end| Some(end + line[end..].find('=')?)) {
+      Some(index) => (line[..index].trim(), line[index + 1..].trim()),
+      None => return Err(AocError::parse(raw, line, "expected key = value").at_line(i + 1)),
+    };
+    if key.is_empty() {
+      return Err(AocError::parse(raw, line, "expected a key").at_line(i + 1));
+    }
+    let key = unquote(key).unwrap_or_else(|| String::from(key));
+    let value = parse_value(value).ok_or_else(|| {
+      AocError::parse(raw, value, "expected an integer or a quoted string").at_line(i + 1)
+    })?;
+    entries.push(Entry {
+      section: section.clone(),
+      key,
+      value,
+      line: i + 1,
+    });
+  }
+
+  Ok(entries)
+}
+
+// `text` as a double-quoted string that `parse` reads back unchanged
+pub fn quote(text: &str) -> String {
+  let mut quoted = String::from("\"");
+  for c in text.chars() {
+    match c {
+      '\n' => quoted.push_str("\\n"),
+      '\t' => quoted.push_str("\\t"),
+      '\\' | '"' => {
+        quoted.push('\\');
+        quoted.push(c);
+      }
+      c => quoted.push(c),
+    }
+  }
+  quoted.push('"');
+  quoted
+}
+
+// Where the key at the start of `line` ends, so that quoted keys may contain `=`
+fn key_end(line: &str) -> Option<usize> {
+  if !line.starts_with('"') {
+    return Some(0);
+  }
+  let mut escaped = false;
+  for (i, c) in line.char_indices().skip(1) {
+    match c {
+      '\\' if !escaped => escaped = true,
+      '"' if !escaped => return Some(i + 1),
+      _ => escaped = false,
+    }
+  }
+  None
+}
+
+fn parse_value(value: &str) -> Option<String> {
+  if value.starts_with('"') {
+    return unquote(value);
+  }
+  // Bare values must be integers, optionally with a trailing comment
+  let value = value.split('#').next().unwrap_or("").trim();
+  let digits = value.strip_prefix('-').unwrap_or(value);
+  if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+    Some(String::from(value))
+  } else {
+    None
+  }
+}
+
+fn unquote(text: &str) -> Option<String> {
+  let mut chars = text.strip_prefix('"')?.chars();
+  let mut value = String::new();
+  loop {
+    match chars.next()? {
+      '"' => break,
+      '\\' => match chars.next()? {
+        'n' => value.push('\n'),
+        't' => value.push('\t'),
+        c @ '\\' | c @ '"' => value.push(c),
+        _ => return None,
+      },
+      c => value.push(c),
+    }
+  }
+  // Only a comment may follow the closing quote
+  let rest = chars.as_str().trim();
+  if rest.is_empty() || rest.starts_with('#') {
+    Some(value)
+  } else {
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn entry(section: &str, key: &str, value: &str, line: usize) -> Entry {
+    Entry {
+      section: String::from(section),
+      key: String::from(key),
+      value: String::from(value),
+      line,
+    }
+  }
+
+  #[test]
+  fn test_parse() {
+    let text = "# top\ninput_dir = \"my inputs\" # comment\n\n[day1]\npart1 = 982464\n\"part 2\" = \"a\\n\\\"b\\\"\"\n";
+    assert_eq!(
+      vec![
+        entry("", "input_dir", "my inputs", 2),
+        entry("day1", "part1", "982464", 5),
+        entry("day1", "part 2", "a\n\"b\"", 6),
+      ],
+      parse(text).unwrap()
+    );
+  }
+
+  #[test]
+  fn test_quote() {
+    for text in &[
+      "",
+      "plain",
+      "a\n\"b\"\t\\c",
+      "# not a comment",
+      "x = 1",
+      "\\",
+    ] {
+      let line = format!("{} = {}", quote(text), quote(text));
+      assert_eq!(vec![entry("", text, text, 1)], parse(&line).unwrap());
+    }
+  }
+
+  #[test]
+  fn test_parse_errors() {
+    for (text, line) in &[
+      ("[day1\n", 1),
+      ("a = 1\nb\n", 2),
+      ("a = unquoted", 1),
+      ("a = \"open", 1),
+    ] {
+      match parse(text) {
+        Err(AocError::Parse { line: l, .. }) => assert_eq!(*line, l, "{:?}", text),
+        other => panic!("expected {:?} to be rejected, got {:?}", text, other),
+      }
+    }
+  }
+}
@@ -1,54 +1,219 @@
+pub use answer::Answer;
+pub use error::AocError;
+pub use solution::{Alternative, Day, Model, Solution};
+use std::sync::Arc;
+use std::time::Duration;
+
 pub struct Input {
   pub day: u32,
   pub part: u32,
   pub input: String,
 }
 
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-
-pub fn run(input: Input) -> Result<i64, Box<dyn std::error::Error>> {
+mod answer;
+pub mod answers;
+pub mod bench;
+pub mod cache;
+pub mod cancel;
+pub mod cli;
+pub mod config;
+pub mod day1;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod difftest;
+mod error;
+pub mod examples;
+#[cfg(test)]
+mod fuzz;
+pub mod generate;
+pub mod history;
+pub mod input;
+pub mod json;
+mod kv;
+pub mod parallel;
+#[cfg(test)]
+mod property;
+pub mod rng;
+pub mod scaffold;
+pub mod site;
+mod solution;
+pub mod source;
+pub mod trace;
+pub mod watch;
+
+static SOLUTIONS: &[&dyn Solution] = &[
+  &day1::Day1,
+  &day2::Day2,
+  &day3::Day3,
+  &day4::Day4,
+  &day5::Day5,
+  &day6::Day6,
+  &day7::Day7,
+  &day8::Day8,
+  &day9::Day9,
+  &day10::Day10,
+  &day11::Day11,
+  &day12::Day12,
+  &day13::Day13,
+  &day14::Day14,
+];
+
+// Every registered day, in order
+pub fn solutions() -> &'static [&'static dyn Solution] {
+  SOLUTIONS
+}
+
+pub fn find(day: u32) -> Option<&'static dyn Solution> {
+  SOLUTIONS
+    .iter()
+    .copied()
+    .find(|solution| solution.day() == day)
+}
+
+// Every (day, part) pair that `run` can solve, in order
+pub fn solved() -> Vec<(u32, u32)> {
+  SOLUTIONS
+    .iter()
+    .flat_map(|solution| {
+      solution
+        .parts()
+        .iter()
+        .map(move |&part| (solution.day(), part))
+    })
+    .collect()
+}
+
+// A day's input parsed once, so that any of its parts can be solved from it
+#[derive(Clone)]
+pub struct Parsed {
+  solution: &'static dyn Solution,
+  model: Arc<Model>,
+}
+
+impl Parsed {
+  pub fn solve(&self, part: u32) -> Result<Answer, AocError> {
+    self.solution.solve_parsed(part, &self.model)
+  }
+
+  // Like `solve`, but cancels the solver once it has taken longer than `timeout`
+  pub fn solve_with_timeout(
+    &self,
+    part: u32,
+    timeout: Option<Duration>,
+  ) -> Result<Answer, AocError> {
+    match timeout {
+      Some(limit) => {
+        let parsed = self.clone();
+        cancel::with_timeout(limit, move || parsed.solve(part))
+      }
+      None => self.solve(part),
+    }
+  }
+}
+
+pub fn parse(day: u32, input: &str) -> Result<Parsed, AocError> {
+  let solution = find(day).ok_or(AocError::DayNotFound(day))?;
+  Ok(Parsed {
+    solution,
+    model: Arc::new(solution.parse(&input::Input::new(input))?),
+  })
+}
+
+// Like `parse`, but cancels the parser once it has taken longer than `timeout`
+pub fn parse_with_timeout(
+  day: u32,
+  input: String,
+  timeout: Option<Duration>,
+) -> Result<Parsed, AocError> {
+  match timeout {
+    Some(limit) => cancel::with_timeout(limit, move || parse(day, &input)),
+    None => parse(day, &input),
+  }
+}
+
+// Like `run`, but cancels the solver once it has taken longer than `timeout`
+pub fn run_with_timeout(input: Input, timeout: Option<Duration>) -> Result<Answer, AocError> {
+  match timeout {
+    Some(limit) => cancel::with_timeout(limit, move || run(input)),
+    None => run(input),
+  }
+}
+
+pub fn run(input: Input) -> Result<Answer, AocError> {
   let Input { day, part, input } = input;
-  match (day, part) {
-    (1, 1) => Ok(day1::part1(&input)?),
-    (1, 2) => Ok(day1::part2(&input)?),
-    (2, 1) => Ok(day2::part1(&input)?),
-    (2, 2) => Ok(day2::part2(&input)?),
-    (3, 1) => Ok(day3::part1(&input)?),
-    (3, 2) => Ok(day3::part2(&input)?),
-    (4, 1) => Ok(day4::part1(&input)?),
-    (4, 2) => Ok(day4::part2(&input)?),
-    (5, 1) => Ok(day5::part1(&input)?),
-    (5, 2) => Ok(day5::part2(&input)?),
-    (6, 1) => Ok(day6::part1(&input)?),
-    (6, 2) => Ok(day6::part2(&input)?),
-    (7, 1) => Ok(day7::part1(&input)?),
-    (7, 2) => Ok(day7::part2(&input)?),
-    (8, 1) => Ok(day8::part1(&input)?),
-    (8, 2) => Ok(day8::part2(&input)?),
-    (9, 1) => Ok(day9::part1(&input)?),
-    (9, 2) => Ok(day9::part2(&input)?),
-    (10, 1) => Ok(day10::part1(&input)?),
-    (10, 2) => Ok(day10::part2(&input)?),
-    (11, 1) => Ok(day11::part1(&input)?),
-    (11, 2) => Ok(day11::part2(&input)?),
-    (12, 1) => Ok(day12::part1(&input)?),
-    (12, 2) => Ok(day12::part2(&input)?),
-    (13, 1) => Ok(day13::part1(&input)?),
-    (13, 2) => Ok(day13::part2(&input)?),
-    (14, 1) => Ok(day14::part1(&input)?),
-    _ => Err(format!("{}-{} not found", day, part).into()),
+  let solution = find(day).ok_or(AocError::DayNotFound(day))?;
+  solution.solve(part, &input::Input::new(&input))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn run_err(day: u32, part: u32) -> AocError {
+    let input = Input {
+      day,
+      part,
+      input: String::new(),
+    };
+    run(input).unwrap_err()
+  }
+
+  // Days are registered in order, once each, though `new` can skip ahead to any day
+  #[test]
+  fn test_registry_is_ordered() {
+    let days: Vec<u32> = solutions().iter().map(|solution| solution.day()).collect();
+    assert!(days.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", days);
+    assert!(days.iter().all(|day| (1..=25).contains(day)), "{:?}", days);
+  }
+
+  #[test]
+  fn test_solved() {
+    let solved = solved();
+    let last = SOLUTIONS.last().unwrap();
+    assert_eq!(Some(&(1, 1)), solved.first());
+    assert_eq!(
+      Some(&(last.day(), *last.parts().last().unwrap())),
+      solved.last()
+    );
+    let parts: usize = SOLUTIONS
+      .iter()
+      .map(|solution| solution.parts().len())
+      .sum();
+    assert_eq!(parts, solved.len());
+    assert!(!solved.contains(&(14, 2)));
+  }
+
+  #[test]
+  fn test_parse_once() {
+    let parsed = parse(1, "1721\n979\n366\n299\n675\n1456").unwrap();
+    assert_eq!(Ok(Answer::Int(514579)), parsed.solve(1));
+    assert_eq!(Ok(Answer::Int(241861950)), parsed.solve(2));
+    assert_eq!(Err(AocError::PartNotImplemented(1, 3)), parsed.solve(3));
+
+    let timeout = Some(Duration::from_secs(10));
+    let parsed = parse_with_timeout(6, String::from("ab\n\nb"), timeout).unwrap();
+    assert_eq!(Ok(Answer::Int(3)), parsed.solve_with_timeout(1, timeout));
+
+    // A model only fits the day that parsed it
+    let model = find(2).unwrap().parse(&input::Input::new("")).unwrap();
+    assert!(find(1).unwrap().solve_parsed(1, &model).is_err());
+  }
+
+  #[test]
+  fn test_not_found() {
+    assert_eq!(AocError::DayNotFound(25), run_err(25, 1));
+    assert_eq!(AocError::PartNotImplemented(14, 2), run_err(14, 2));
+    assert_eq!(AocError::PartNotImplemented(1, 3), run_err(1, 3));
   }
 }
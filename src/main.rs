@@ -1,30 +1,836 @@
-use aoc2020;
+use aoc2020::answers::{self, KnownAnswers, Verdict};
+use aoc2020::bench::{self, Baseline, Stats};
+use aoc2020::cache::{self, Cache, Entry};
+use aoc2020::cli::{self, Args, Command, Format};
+use aoc2020::config::{self, Config};
+use aoc2020::difftest;
+use aoc2020::generate;
+use aoc2020::history::History;
+use aoc2020::input::Input;
+use aoc2020::json::Value;
+use aoc2020::site::{Fetched, Outcome, Site};
+use aoc2020::source::InputSource;
+use aoc2020::{parallel, scaffold, trace, watch};
+use aoc2020::{Answer, AocError, Parsed};
 use std::fs;
+use std::path::Path;
 use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+struct Row {
+    day: u32,
+    part: u32,
+    answer: Result<Answer, AocError>,
+    // None when the part reused the model parsed for an earlier part of the day
+    parse: Option<Duration>,
+    solve: Duration,
+    // Where the input came from, and its hash if it could be read
+    input: String,
+    hash: Option<String>,
+    // Whether the answer came from the cache rather than the solver
+    cached: bool,
+}
+
+// How to run parts and report on them, from the command line options
+#[derive(Clone, Copy)]
+struct Settings {
+    format: Format,
+    jobs: usize,
+    timeout: Option<Duration>,
+}
 
 fn main() {
-    let mut args = std::env::args();
-    args.next();
-
-    let day: u32 = args
-        .next()
-        .expect("Must supply the puzzle day")
-        .parse()
-        .expect("Cannot parse a number from the day provided");
-    let part: u32 = args
-        .next()
-        .expect("Must supply the puzzle part")
-        .parse()
-        .expect("Cannot parse a number from the part provided");
-
-    let input = fs::read_to_string(format!("input/day{}.txt", day)).unwrap_or_else(|e| {
-        eprintln!("unable to read file: {}", e);
-        process::exit(1);
+    let args = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, cli::USAGE);
+            process::exit(err.exit_code());
+        }
+    };
+
+    let trace = args.trace.clone();
+    let mut code = match run(args) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            err.exit_code()
+        }
+    };
+    if let Some(path) = trace.filter(|_| trace::ENABLED) {
+        if let Err(err) = write_trace(Path::new(&path)) {
+            eprintln!("error: {}", err);
+            if code == 0 {
+                code = err.exit_code();
+            }
+        }
+    }
+    process::exit(code);
+}
+
+// Runs `command`, returning the exit code once any output has been printed
+fn run(args: Args) -> Result<i32, AocError> {
+    if args.trace.is_some() && !trace::ENABLED {
+        return Err(AocError::InvalidArgument(String::from(
+            "--trace needs a build with --features trace",
+        )));
+    }
+    let config = Config::load()?;
+    let source = InputSource::resolve(args.input.as_deref(), &config);
+    let settings = Settings {
+        format: args.format,
+        jobs: args.jobs,
+        timeout: args.timeout,
+    };
+    // Cached parts wouldn't show up in a trace
+    let cache = match args.no_cache || args.trace.is_some() {
+        true => None,
+        false => Some(config.cache.clone().unwrap_or_else(Cache::default_path)),
+    };
+    let cache = cache.as_deref();
+    match args.command {
+        Command::Help => println!("{}", cli::USAGE),
+        Command::List => {
+            for solution in aoc2020::solutions() {
+                let parts: Vec<String> = solution.parts().iter().map(|p| p.to_string()).collect();
+                println!(
+                    "{:>3}  {:<20}  {}",
+                    solution.day(),
+                    solution.title(),
+                    parts.join(",")
+                );
+            }
+        }
+        Command::All => return run_pairs(&source, &aoc2020::solved(), settings, cache),
+        Command::Verify => {
+            let path = config.answers.unwrap_or_else(KnownAnswers::default_path);
+            let answers = KnownAnswers::load(&path)?;
+            return Ok(verify(&source, &answers, settings));
+        }
+        Command::New { day } => {
+            let input = source
+                .path(day)
+                .ok_or_else(|| AocError::InvalidArgument(String::from("no input directory")))?;
+            for path in scaffold::create(Path::new(config::ROOT), &input, day)? {
+                println!("created {}", path.display());
+            }
+            println!(
+                "Paste the puzzle input into the input file and the example and its answer \
+                 into the example file, then record the answer in answers.toml once it's \
+                 accepted."
+            );
+        }
+        Command::Fetch { day } => {
+            let path = source
+                .path(day)
+                .ok_or_else(|| AocError::InvalidArgument(String::from("no input directory")))?;
+            match Site::from_config(&config).fetch(day, &path)? {
+                Fetched::Downloaded => println!("downloaded {}", path.display()),
+                Fetched::Cached => println!("{} already exists, not fetching", path.display()),
+            }
+        }
+        Command::Submit { day, part } => {
+            let path = config.history.clone().unwrap_or_else(History::default_path);
+            return submit(&source, &config, &path, day, part);
+        }
+        Command::Watch { day } => watch(&source, day, args.timeout)?,
+        Command::DiffTest { day, options } => return diff_test(day, &options),
+        Command::Gen { day, options } => {
+            let solution = aoc2020::find(day).ok_or(AocError::DayNotFound(day))?;
+            print!("{}", generate::generate(solution, &options)?);
+        }
+        Command::ClearCache => {
+            let path = config.cache.unwrap_or_else(Cache::default_path);
+            let count = cache::clear(&path)?;
+            println!(
+                "forgot {} cached {}",
+                count,
+                if count == 1 { "answer" } else { "answers" }
+            );
+        }
+        Command::Bench { day, part, options } => {
+            let pairs = match day {
+                Some(day) => select(day, part)?,
+                None => aoc2020::solved(),
+            };
+            let path = config.baseline.unwrap_or_else(Baseline::default_path);
+            return run_bench(&source, &pairs, &options, &path);
+        }
+        Command::Run { day, part: None } => {
+            return run_pairs(&source, &select(day, None)?, settings, cache)
+        }
+        Command::Run {
+            day,
+            part: Some(part),
+        } => {
+            let pairs = select(day, Some(part))?;
+            if args.format == Format::Json {
+                return run_pairs(&source, &pairs, settings, cache);
+            }
+            let row = run_days(&source, &pairs, settings, cache)?.remove(0);
+            println!("{}", row.answer?);
+            if row.cached {
+                eprintln!("(cached, run with --no-cache to solve it again)");
+            }
+        }
+    }
+    Ok(0)
+}
+
+// Writes the spans recorded while running, and prints each span's totals to stderr
+fn write_trace(path: &Path) -> Result<(), AocError> {
+    let trace = trace::take();
+    let text = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => trace.to_chrome(),
+        _ => trace.to_folded(),
+    };
+    fs::write(path, text).map_err(|e| AocError::Io(format!("{}: {}", path.display(), e)))?;
+    eprintln!("wrote {} spans to {}", trace.len(), path.display());
+    eprintln!("{:<24} {:>8} {:>10}  counts", "span", "calls", "own time");
+    for total in trace.summary() {
+        let counts: Vec<String> = total
+            .counts
+            .iter()
+            .map(|(name, n)| format!("{} {}", n, name))
+            .collect();
+        let line = format!(
+            "{:<24} {:>8} {:>10}  {}",
+            total.name,
+            total.calls,
+            format_duration(total.own),
+            counts.join(", ")
+        );
+        eprintln!("{}", line.trim_end());
+    }
+    Ok(())
+}
+
+// Solves one part and sends the answer, unless the history already says how that
+// would go
+fn submit(
+    source: &InputSource,
+    config: &Config,
+    path: &Path,
+    day: u32,
+    part: u32,
+) -> Result<i32, AocError> {
+    select(day, Some(part))?;
+    let mut history = History::load(path)?;
+    let input = source.read(day)?;
+    let answer = aoc2020::run(aoc2020::Input { day, part, input })?.to_string();
+    if answer.is_empty() || answer.contains('\n') {
+        return Err(AocError::InvalidArgument(format!(
+            "day {} part {} has an answer that can't be submitted:\n{}",
+            day, part, answer
+        )));
+    }
+    if let Some(reason) = history.verdict(day, part, &answer) {
+        println!("not submitting {}: {}", answer, reason);
+        return Ok(1);
+    }
+
+    let outcome = Site::from_config(config).submit(day, part, &answer)?;
+    if let Outcome::RateLimited(_) | Outcome::Unknown(_) = outcome {
+        return Err(AocError::Http(outcome.to_string()));
+    }
+    println!("submitted {}: {}", answer, outcome);
+    let code = match outcome {
+        Outcome::Right => {
+            println!(
+                "add it to answers.toml as part{} under [day{}] so verify checks it",
+                part, day
+            );
+            0
+        }
+        Outcome::AlreadySolved => 0,
+        _ => 1,
+    };
+    history.record(day, part, &answer, outcome);
+    history.save(path)?;
+    Ok(code)
+}
+
+// Re-runs the day's examples and parts whenever its input or source changes, until
+// interrupted
+fn watch(source: &InputSource, day: u32, timeout: Option<Duration>) -> Result<(), AocError> {
+    select(day, None)?;
+    let input = source.path(day).ok_or_else(|| {
+        AocError::InvalidArgument(String::from("watch needs an input file, not stdin"))
+    })?;
+    let root = Path::new(config::ROOT);
+    let paths = vec![input.clone(), root.join(format!("src/day{}.rs", day))];
+    let mut answers: Vec<Option<Result<String, String>>> = vec![None, None];
+    let mut stamp: Option<watch::Stamp> = None;
+    println!(
+        "watching {}",
+        paths
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" and ")
+    );
+    loop {
+        let current = watch::Stamp::new(&paths);
+        let changed = match &stamp {
+            Some(previous) if *previous == current => {
+                thread::sleep(watch::POLL);
+                continue;
+            }
+            Some(previous) => previous.changed(&current, &paths),
+            None => Vec::new(),
+        };
+        stamp = Some(current);
+        for path in changed {
+            println!("\n{} changed", path.display());
+        }
+
+        match watch::run_examples(root, day) {
+            Ok(()) => println!("examples: passed"),
+            Err(output) => println!("examples: failed\n{}", output),
+        }
+        for part in 1..=2 {
+            let result = watch::run_part(root, day, part, &input, timeout);
+            if let Some(result) = &result {
+                let previous = answers[part as usize - 1].as_ref();
+                for line in watch::diff(part, previous, result) {
+                    println!("{}", line);
+                }
+            }
+            answers[part as usize - 1] = result;
+        }
+    }
+}
+
+// Compares the implementations of each part that has more than one, returning 1 if any
+// of them disagreed
+fn diff_test(day: Option<u32>, options: &difftest::Options) -> Result<i32, AocError> {
+    let mut pairs = difftest::parts();
+    if let Some(day) = day {
+        select(day, None)?;
+        pairs.retain(|&(d, _)| d == day);
+        if pairs.is_empty() {
+            return Err(AocError::InvalidArgument(format!(
+                "day {} has only one implementation of each part",
+                day
+            )));
+        }
+    }
+    let mut code = 0;
+    for (day, part) in pairs {
+        let solution = aoc2020::find(day).ok_or(AocError::DayNotFound(day))?;
+        let cases = difftest::cases(solution, options)?;
+        let report = difftest::compare(solution, part, &cases, options.timeout);
+        println!(
+            "day {} part {}: {}",
+            day,
+            part,
+            report.implementations.join(" vs ")
+        );
+        let skipped = match report.skipped {
+            0 => String::new(),
+            n => format!(", skipped {} that failed to parse or timed out", n),
+        };
+        match report.disagreement {
+            None => println!("  agreed on {} inputs{}", report.agreed, skipped),
+            Some((case, results)) => {
+                code = 1;
+                println!(
+                    "  disagreed on {}, after agreeing on {}:",
+                    case.label, report.agreed
+                );
+                for (name, result) in results {
+                    let result = match result {
+                        Ok(answer) => answer.to_string(),
+                        Err(err) => format!("error: {}", err),
+                    };
+                    println!("    {}: {}", name, result);
+                }
+                println!("  input:");
+                for line in case.input.lines() {
+                    println!("    {}", line);
+                }
+            }
+        }
+    }
+    Ok(code)
+}
+
+// The (day, part) pairs to run for a day, or one part of it
+fn select(day: u32, part: Option<u32>) -> Result<Vec<(u32, u32)>, AocError> {
+    let solution = aoc2020::find(day).ok_or(AocError::DayNotFound(day))?;
+    match part {
+        Some(part) if !solution.parts().contains(&part) => {
+            Err(AocError::PartNotImplemented(day, part))
+        }
+        Some(part) => Ok(vec![(day, part)]),
+        None => Ok(solution.parts().iter().map(|&p| (day, p)).collect()),
+    }
+}
+
+impl Row {
+    fn total(&self) -> Duration {
+        self.parse.unwrap_or_default() + self.solve
+    }
+}
+
+// `pairs` grouped by day, keeping their order
+fn by_day(pairs: &[(u32, u32)]) -> Vec<(u32, Vec<u32>)> {
+    let mut days: Vec<(u32, Vec<u32>)> = Vec::new();
+    for &(day, part) in pairs {
+        match days.last_mut() {
+            Some((last, parts)) if *last == day => parts.push(part),
+            _ => days.push((day, vec![part])),
+        }
+    }
+    days
+}
+
+// One day's input, parsed once for all of its parts that aren't already cached
+struct Prepared {
+    day: u32,
+    parts: Vec<u32>,
+    // The cached answer for each of `parts`, if there is one
+    cached: Vec<Option<Entry>>,
+    // None when every part is cached, so the input wasn't parsed
+    parsed: Option<Result<Parsed, AocError>>,
+    parse: Duration,
+    input: String,
+    hash: Option<String>,
+}
+
+// Reads and parses `day`'s input for `parts`, with the timeout applying to the parse.
+// Parts with an answer in `cache` for this input and solver won't be solved, and if
+// every part has one the input isn't parsed either.
+fn prepare_day(
+    source: &InputSource,
+    day: u32,
+    parts: &[u32],
+    timeout: Option<Duration>,
+    cache: Option<&Cache>,
+) -> Prepared {
+    let input = source.read(day);
+    let hash = input.as_ref().ok().map(|raw| Input::new(raw).hash());
+    let version = aoc2020::find(day).map(|solution| solution.version());
+    let cached: Vec<Option<Entry>> = parts
+        .iter()
+        .map(|&part| match (cache, &hash, &version) {
+            (Some(cache), Some(hash), Some(version)) => {
+                cache.get(day, part, hash, version).cloned()
+            }
+            _ => None,
+        })
+        .collect();
+
+    let start = Instant::now();
+    let parsed = match cached.iter().all(Option::is_some) {
+        true => None,
+        false => Some(input.and_then(|input| aoc2020::parse_with_timeout(day, input, timeout))),
+    };
+    Prepared {
+        day,
+        parts: parts.to_vec(),
+        cached,
+        parsed,
+        parse: start.elapsed(),
+        input: source.describe(day),
+        hash,
+    }
+}
+
+// Solves the `index`th of a prepared day's parts from its shared model, with the
+// timeout applying to this part alone. The day's parse time goes to its first part
+// that wasn't cached, and cached parts keep the timings they were first solved in.
+fn solve_part(prepared: &Prepared, index: usize, timeout: Option<Duration>) -> Row {
+    let part = prepared.parts[index];
+    let first = prepared.cached.iter().position(Option::is_none) == Some(index);
+    let start = Instant::now();
+    let (answer, parse, solve, cached) = match (&prepared.cached[index], &prepared.parsed) {
+        (Some(entry), _) => (Ok(entry.answer.clone()), entry.parse, entry.solve, true),
+        (None, parsed) => {
+            let answer = match parsed {
+                Some(Ok(parsed)) => parsed.solve_with_timeout(part, timeout),
+                Some(Err(err)) => Err(err.clone()),
+                None => unreachable!("the input is parsed unless every part is cached"),
+            };
+            let parse = if first { Some(prepared.parse) } else { None };
+            (answer, parse, start.elapsed(), false)
+        }
+    };
+    Row {
+        day: prepared.day,
+        part,
+        answer,
+        parse,
+        solve,
+        input: prepared.input.clone(),
+        hash: prepared.hash.clone(),
+        cached,
+    }
+}
+
+// Parses each day in `pairs` once, then solves every part from its day's model, with up
+// to `jobs` days or parts in flight at once. The rows come back in the order of `pairs`.
+fn run_parts(
+    source: &InputSource,
+    pairs: &[(u32, u32)],
+    settings: Settings,
+    cache: Option<&Cache>,
+) -> Vec<Row> {
+    let days = parallel::map(&by_day(pairs), settings.jobs, |(day, parts)| {
+        prepare_day(source, *day, parts, settings.timeout, cache)
     });
+    let parts: Vec<(&Prepared, usize)> = days
+        .iter()
+        .flat_map(|prepared| (0..prepared.parts.len()).map(move |index| (prepared, index)))
+        .collect();
+    parallel::map(&parts, settings.jobs, |&(prepared, index)| {
+        solve_part(prepared, index, settings.timeout)
+    })
+}
+
+// Runs `pairs`, up to `jobs` days or parts at a time. With a `cache` path, answers
+// cached there are reused and new ones are added to it.
+fn run_days(
+    source: &InputSource,
+    pairs: &[(u32, u32)],
+    settings: Settings,
+    cache: Option<&Path>,
+) -> Result<Vec<Row>, AocError> {
+    let mut answers = match cache {
+        Some(path) => Some(Cache::load(path)?),
+        None => None,
+    };
+    let rows = run_parts(source, pairs, settings, answers.as_ref());
+
+    if let (Some(path), Some(answers)) = (cache, &mut answers) {
+        let mut changed = false;
+        for row in rows.iter().filter(|row| !row.cached) {
+            let solution = aoc2020::find(row.day);
+            if let (Ok(answer), Some(hash), Some(solution)) = (&row.answer, &row.hash, solution) {
+                let entry = Entry {
+                    answer: answer.clone(),
+                    parse: row.parse,
+                    solve: row.solve,
+                };
+                answers.insert(row.day, row.part, hash, &solution.version(), entry);
+                changed = true;
+            }
+        }
+        if changed {
+            answers.save(path)?;
+        }
+    }
+    Ok(rows)
+}
+
+// Runs `pairs`, up to `jobs` days or parts at a time, and prints the results in
+// `format`, and any failures to stderr. Returns the exit code for the first failure, or
+// 0 if they all succeeded.
+fn run_pairs(
+    source: &InputSource,
+    pairs: &[(u32, u32)],
+    settings: Settings,
+    cache: Option<&Path>,
+) -> Result<i32, AocError> {
+    let Settings { format, jobs, .. } = settings;
+    let (start, cpu_start) = (Instant::now(), parallel::cpu_time());
+    let rows = run_days(source, pairs, settings, cache)?;
+    let wall = start.elapsed();
+    // Without a CPU clock, fall back to the time spent in each part, which overcounts
+    // when there are more jobs than cores
+    let cpu = match (cpu_start, parallel::cpu_time()) {
+        (Some(start), Some(end)) => end.saturating_sub(start),
+        _ => rows.iter().filter(|row| !row.cached).map(Row::total).sum(),
+    };
+    match format {
+        Format::Text => {
+            print_table(&rows);
+            println!(
+                "wall-clock {}, cpu {} ({} {})",
+                format_duration(wall),
+                format_duration(cpu),
+                jobs,
+                if jobs == 1 { "job" } else { "jobs" }
+            );
+        }
+        Format::Json => {
+            for row in &rows {
+                println!("{}", to_json(row));
+            }
+        }
+    }
+
+    let mut code = 0;
+    for row in &rows {
+        if let Err(err) = &row.answer {
+            eprintln!("error: day {} part {}: {}", row.day, row.part, err);
+            if code == 0 {
+                code = err.exit_code();
+            }
+        }
+    }
+    Ok(code)
+}
+
+fn to_json(row: &Row) -> Value {
+    let (answer, error) = match &row.answer {
+        Ok(answer) => (Value::from(answer.to_string()), Value::Null),
+        Err(err) => (Value::Null, Value::from(err)),
+    };
+    let timings = Value::object()
+        .with("parse_ns", row.parse.map(|parse| parse.as_nanos()))
+        .with("solve_ns", row.solve.as_nanos())
+        .with("total_ns", row.total().as_nanos());
+    let input = Value::object()
+        .with("path", row.input.as_str())
+        .with("hash", row.hash.clone());
+    Value::object()
+        .with("day", row.day)
+        .with("part", row.part)
+        .with("answer", answer)
+        .with("cached", row.cached)
+        .with("timings", timings)
+        .with("input", input)
+        .with("error", error)
+}
+
+// Prints a timing table for `rows`
+fn print_table(rows: &[Row]) {
+    let answers: Vec<String> = rows
+        .iter()
+        .map(|row| match &row.answer {
+            Ok(answer) => answer.to_string(),
+            Err(_) => String::from("error"),
+        })
+        .collect();
+    let width = answers
+        .iter()
+        .flat_map(|answer| answer.lines())
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("answer".len());
+
+    println!(
+        "{:>3} {:>4}  {:<width$}  {:>10} {:>10} {:>10}",
+        "day",
+        "part",
+        "answer",
+        "parse",
+        "solve",
+        "total",
+        width = width
+    );
+    let mut total = Duration::default();
+    for (row, answer) in rows.iter().zip(&answers) {
+        total += row.total();
+        // Multi-line answers continue underneath their row
+        let mut lines = answer.lines();
+        // Cached rows show the timings from the run that solved them
+        println!(
+            "{:>3} {:>4}  {:<width$}  {:>10} {:>10} {:>10}{}",
+            row.day,
+            row.part,
+            lines.next().unwrap_or(""),
+            row.parse.map_or(String::from("shared"), format_duration),
+            format_duration(row.solve),
+            format_duration(row.total()),
+            if row.cached { "  cached" } else { "" },
+            width = width
+        );
+        for line in lines {
+            println!("{:>3} {:>4}  {}", "", "", line);
+        }
+    }
+    println!(
+        "{:>3} {:>4}  {:<width$}  {:>10} {:>10} {:>10}",
+        "",
+        "",
+        "",
+        "",
+        "",
+        format_duration(total),
+        width = width
+    );
+}
+
+// Checks every registered solver, and every known answer, printing one line for each.
+// Returns 1 if any solver failed or gave the wrong answer.
+fn verify(source: &InputSource, answers: &KnownAnswers, settings: Settings) -> i32 {
+    let solved = aoc2020::solved();
+    let mut results = run_parts(source, &solved, settings, None)
+        .into_iter()
+        .map(|row| row.answer);
+    let mut pairs = solved.clone();
+    pairs.extend(answers.pairs());
+    pairs.sort_unstable();
+    pairs.dedup();
+
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+    for (day, part) in pairs {
+        let expected = answers.get(day, part);
+        let (verdict, detail) = if !solved.contains(&(day, part)) {
+            (
+                Verdict::Missing,
+                format!("no solver for known answer {}", expected.unwrap_or("")),
+            )
+        } else {
+            // `pairs` has the solved pairs in the same order as `results`
+            let actual = results.next().expect("a result for every solved part");
+            let verdict = answers::check(expected, &actual);
+            let actual = match actual {
+                Ok(answer) => answer.to_string(),
+                Err(err) => format!("error: {}", err),
+            };
+            let detail = match (&verdict, expected) {
+                (Verdict::Missing, _) => format!("no known answer, found {}", actual),
+                (Verdict::Fail, Some(expected)) => {
+                    format!("expected {}, found {}", expected, actual)
+                }
+                _ => actual,
+            };
+            (verdict, detail)
+        };
+        let label = match verdict {
+            Verdict::Pass => {
+                passed += 1;
+                "pass"
+            }
+            Verdict::Fail => {
+                failed += 1;
+                "FAIL"
+            }
+            Verdict::Missing => {
+                missing += 1;
+                "missing"
+            }
+        };
+        println!("{:>3} {:>4}  {:<7}  {}", day, part, label, detail);
+    }
+    println!("{} passed, {} failed, {} missing", passed, failed, missing);
+
+    if failed > 0 {
+        1
+    } else {
+        0
+    }
+}
+
+// Benchmarks the parse of each day in `pairs`, then each of `pairs` from a model parsed
+// once, and compares the medians against the baseline at `path`, saving them as the
+// new baseline if asked. Returns the exit code for the first solver that failed, or 1
+// if any regressed.
+fn run_bench(
+    source: &InputSource,
+    pairs: &[(u32, u32)],
+    options: &bench::Options,
+    path: &Path,
+) -> Result<i32, AocError> {
+    let mut baseline = Baseline::load(path)?;
+    println!(
+        "{:>3} {:>5}  {:>10} {:>10} {:>10} {:>10}  {:>12}  baseline",
+        "day", "part", "min", "median", "mean", "stddev", "throughput"
+    );
+
+    let (mut code, mut regressions) = (0, 0);
+    for (day, parts) in by_day(pairs) {
+        let raw = source.read(day);
+        let parsed = raw
+            .as_ref()
+            .map_err(|err| err.clone())
+            .and_then(|raw| aoc2020::parse(day, raw));
+        let bytes = raw.as_ref().map_or(0, |raw| raw.len());
+        let mut results = vec![(
+            bench::PARSE,
+            raw.and_then(|raw| bench::sample(options, || aoc2020::parse(day, &raw))),
+        )];
+        for part in parts {
+            let samples = match &parsed {
+                Ok(parsed) => bench::sample(options, || parsed.solve(part)),
+                Err(err) => Err(err.clone()),
+            };
+            results.push((part, samples));
+        }
+
+        for (part, samples) in results {
+            let label = match part {
+                bench::PARSE => String::from("parse"),
+                part => part.to_string(),
+            };
+            let stats = match samples {
+                Ok(samples) => Stats::new(&samples),
+                Err(err) => {
+                    eprintln!("error: day {} {}: {}", day, label, err);
+                    if code == 0 {
+                        code = err.exit_code();
+                    }
+                    continue;
+                }
+            };
+            let comparison = match baseline.get(day, part) {
+                Some(previous) => {
+                    let change = bench::change(previous, stats.median);
+                    if change > options.threshold {
+                        regressions += 1;
+                        format!("{:+.1}% regressed", change)
+                    } else {
+                        format!("{:+.1}%", change)
+                    }
+                }
+                None => String::from("-"),
+            };
+            println!(
+                "{:>3} {:>5}  {:>10} {:>10} {:>10} {:>10}  {:>12}  {}",
+                day,
+                label,
+                format_duration(stats.min),
+                format_duration(stats.median),
+                format_duration(stats.mean),
+                format_duration(stats.stddev),
+                format_throughput(stats.throughput(bytes)),
+                comparison
+            );
+            if options.save {
+                baseline.insert(day, part, stats.median);
+            }
+        }
+    }
+
+    if regressions > 0 {
+        println!(
+            "{} regressed by more than {}% against {}",
+            regressions,
+            options.threshold,
+            path.display()
+        );
+    }
+    if options.save {
+        baseline.save(path)?;
+        println!("saved baseline to {}", path.display());
+    }
+    Ok(match code {
+        0 if regressions > 0 => 1,
+        code => code,
+    })
+}
+
+fn format_throughput(bytes_per_sec: f64) -> String {
+    if bytes_per_sec >= 1e9 {
+        format!("{:.1} GB/s", bytes_per_sec / 1e9)
+    } else if bytes_per_sec >= 1e6 {
+        format!("{:.1} MB/s", bytes_per_sec / 1e6)
+    } else {
+        format!("{:.1} kB/s", bytes_per_sec / 1e3)
+    }
+}
 
-    let output = aoc2020::run(aoc2020::Input { day, part, input });
-    match output {
-        Ok(val) => println!("{}", val),
-        Err(err) => println!("Got err: {}", err),
+fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_micros();
+    if micros < 1_000 {
+        format!("{}µs", micros)
+    } else if micros < 1_000_000 {
+        format!("{:.2}ms", micros as f64 / 1_000.0)
+    } else {
+        format!("{:.2}s", micros as f64 / 1_000_000.0)
     }
 }
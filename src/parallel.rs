@@ -0,0 +1,107 @@
+// Runs independent work on up to `jobs` scoped threads, keeping results in input order
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+// CPU time used by every thread of this process so far, where the platform reports it
+#[cfg(unix)]
+pub fn cpu_time() -> Option<Duration> {
+  let mut time = libc::timespec {
+    tv_sec: 0,
+    tv_nsec: 0,
+  };
+  // Safe: `time` is a valid timespec for the duration of the call
+  match unsafe { libc::clock_gettime(libc::CLOCK_PROCESS_CPUTIME_ID, &mut time) } {
+    0 => Some(Duration::new(time.tv_sec as u64, time.tv_nsec as u32)),
+    _ => None,
+  }
+}
+
+#[cfg(not(unix))]
+pub fn cpu_time() -> Option<Duration> {
+  None
+}
+
+// Applies `f` to every item, with up to `jobs` items in flight at once. Workers take the
+// next unstarted item as they finish, so one slow item doesn't hold up the rest.
+pub fn map<T, R, F>(items: &[T], jobs: usize, f: F) -> Vec<R>
+where
+  T: Sync,
+  R: Send,
+  F: Fn(&T) -> R + Sync,
+{
+  let jobs = jobs.clamp(1, items.len().max(1));
+  if jobs == 1 {
+    return items.iter().map(f).collect();
+  }
+
+  let next = AtomicUsize::new(0);
+  let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+    let workers: Vec<_> = (0..jobs)
+      .map(|_| {
+        scope.spawn(|| {
+          let mut done = Vec::new();
+          loop {
+            let index = next.fetch_add(1, Ordering::Relaxed);
+            match items.get(index) {
+              Some(item) => done.push((index, f(item))),
+              None => return done,
+            }
+          }
+        })
+      })
+      .collect();
+    workers
+      .into_iter()
+      .flat_map(|worker| match worker.join() {
+        Ok(done) => done,
+        Err(panic) => std::panic::resume_unwind(panic),
+      })
+      .collect()
+  });
+  results.sort_unstable_by_key(|&(index, _)| index);
+  results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::HashSet;
+  use std::sync::Mutex;
+
+  #[test]
+  fn test_map_keeps_order() {
+    let items: Vec<u64> = (0..50).collect();
+    for jobs in &[0, 1, 4, 100] {
+      let squares = map(&items, *jobs, |&n| {
+        // Finish out of order
+        thread::sleep(Duration::from_micros((50 - n) * 20));
+        n * n
+      });
+      assert_eq!(items.iter().map(|n| n * n).collect::<Vec<u64>>(), squares);
+    }
+    assert!(map(&[] as &[u32], 4, |&n| n).is_empty());
+  }
+
+  #[test]
+  #[cfg(unix)]
+  fn test_cpu_time() {
+    let before = cpu_time().unwrap();
+    let mut n = 0u64;
+    while cpu_time().unwrap() == before {
+      n = n.wrapping_add(1);
+    }
+    assert!(cpu_time().unwrap() > before, "{}", n);
+  }
+
+  #[test]
+  fn test_map_uses_threads() {
+    let threads = Mutex::new(HashSet::new());
+    map(&[(); 8], 4, |_| {
+      thread::sleep(Duration::from_millis(10));
+      threads.lock().unwrap().insert(thread::current().id());
+    });
+    assert!(threads.into_inner().unwrap().len() > 1);
+  }
+}
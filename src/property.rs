@@ -0,0 +1,238 @@
+// Property tests: checks that something holds for many random values, and when it
+// doesn't, shrinks the value that broke it to a minimal one before failing, e.g. the
+// single adapter that shows a bug rather than the twelve it was found with.
+//
+// A `Strategy` generates values from a seeded `Rng`, so runs are repeatable, and lists
+// smaller values to try in place of a failing one. Properties return `Err` with a
+// message rather than panicking, so that shrinking can try more values.
+
+use crate::rng::Rng;
+use std::fmt::Debug;
+
+// How many values each property is checked on
+pub const CASES: usize = 256;
+
+// Shrinking gives up after trying this many values, keeping the smallest failure so far
+const MAX_SHRINKS: usize = 10_000;
+
+pub trait Strategy {
+  type Value: Clone + Debug;
+
+  fn generate(&self, rng: &mut Rng) -> Self::Value;
+
+  // Smaller values to try in place of `value`, the most promising first
+  fn shrink(&self, value: &Self::Value) -> Vec<Self::Value>;
+}
+
+// Panics with the smallest value found that breaks `property`, if any value does
+pub fn check<S, F>(strategy: &S, property: F)
+where
+  S: Strategy,
+  F: Fn(&S::Value) -> Result<(), String>,
+{
+  let mut rng = Rng::new(0);
+  for case in 0..CASES {
+    let value = strategy.generate(&mut rng);
+    if let Err(message) = property(&value) {
+      let (minimal, message, steps) = minimize(strategy, &property, value.clone(), message);
+      panic!(
+        "property failed on case {}: {}\n  minimal value: {:?}\n  shrunk in {} steps from: {:?}",
+        case + 1,
+        message,
+        minimal,
+        steps,
+        value
+      );
+    }
+  }
+}
+
+// Repeatedly replaces `value` with the first smaller value that still fails
+fn minimize<S, F>(
+  strategy: &S,
+  property: &F,
+  mut value: S::Value,
+  mut message: String,
+) -> (S::Value, String, usize)
+where
+  S: Strategy,
+  F: Fn(&S::Value) -> Result<(), String>,
+{
+  let mut steps = 0;
+  let mut tries = 0;
+  'shrinking: while tries < MAX_SHRINKS {
+    for candidate in strategy.shrink(&value) {
+      tries += 1;
+      if let Err(failure) = property(&candidate) {
+        value = candidate;
+        message = failure;
+        steps += 1;
+        continue 'shrinking;
+      }
+      if tries >= MAX_SHRINKS {
+        break;
+      }
+    }
+    break;
+  }
+  (value, message, steps)
+}
+
+// Whole numbers from `low` to `high` inclusive, shrinking towards `low`
+pub struct Ints {
+  pub low: i64,
+  pub high: i64,
+}
+
+impl Strategy for Ints {
+  type Value = i64;
+
+  fn generate(&self, rng: &mut Rng) -> i64 {
+    rng.range(self.low, self.high)
+  }
+
+  fn shrink(&self, &value: &i64) -> Vec<i64> {
+    let mut smaller = vec![self.low, self.low + (value - self.low) / 2, value - 1];
+    smaller.dedup();
+    smaller.retain(|&n| n >= self.low && n < value);
+    smaller
+  }
+}
+
+// One of a fixed list, shrinking towards the earlier ones
+pub struct OneOf<T>(pub Vec<T>);
+
+impl<T: Clone + Debug + PartialEq> Strategy for OneOf<T> {
+  type Value = T;
+
+  fn generate(&self, rng: &mut Rng) -> T {
+    rng.pick(&self.0).clone()
+  }
+
+  fn shrink(&self, value: &T) -> Vec<T> {
+    let index = self.0.iter().position(|item| item == value).unwrap_or(0);
+    self.0[..index].to_vec()
+  }
+}
+
+// Lists of `min` to `max` values, shrinking by dropping values and then by shrinking
+// each one
+pub struct Vecs<S> {
+  pub item: S,
+  pub min: usize,
+  pub max: usize,
+}
+
+impl<S: Strategy> Strategy for Vecs<S> {
+  type Value = Vec<S::Value>;
+
+  fn generate(&self, rng: &mut Rng) -> Self::Value {
+    let len = rng.range(self.min as i64, self.max as i64) as usize;
+    (0..len).map(|_| self.item.generate(rng)).collect()
+  }
+
+  fn shrink(&self, value: &Self::Value) -> Vec<Self::Value> {
+    let mut smaller = Vec::new();
+    // The first or second half alone, then each value left out in turn
+    let half = value.len() / 2;
+    if half >= self.min && half > 0 {
+      smaller.push(value[..half].to_vec());
+      smaller.push(value[value.len() - half..].to_vec());
+    }
+    if value.len() > self.min {
+      for i in 0..value.len() {
+        let mut fewer = value.clone();
+        fewer.remove(i);
+        smaller.push(fewer);
+      }
+    }
+    for (i, item) in value.iter().enumerate() {
+      for shrunk in self.item.shrink(item) {
+        let mut changed = value.clone();
+        changed[i] = shrunk;
+        smaller.push(changed);
+      }
+    }
+    smaller
+  }
+}
+
+// Pairs, shrinking the first value and then the second
+impl<A: Strategy, B: Strategy> Strategy for (A, B) {
+  type Value = (A::Value, B::Value);
+
+  fn generate(&self, rng: &mut Rng) -> Self::Value {
+    let first = self.0.generate(rng);
+    (first, self.1.generate(rng))
+  }
+
+  fn shrink(&self, (a, b): &Self::Value) -> Vec<Self::Value> {
+    let firsts = self.0.shrink(a).into_iter().map(|a| (a, b.clone()));
+    let seconds = self.1.shrink(b).into_iter().map(|b| (a.clone(), b));
+    firsts.chain(seconds).collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::panic;
+
+  fn failure<F: FnOnce() + panic::UnwindSafe>(f: F) -> String {
+    let payload = panic::catch_unwind(f).unwrap_err();
+    match payload.downcast::<String>() {
+      Ok(message) => *message,
+      Err(_) => String::from("a panic without a message"),
+    }
+  }
+
+  #[test]
+  fn test_passes() {
+    let strategy = Vecs {
+      item: Ints { low: 0, high: 9 },
+      min: 0,
+      max: 5,
+    };
+    check(&strategy, |numbers| {
+      match numbers.iter().sum::<i64>() <= 45 {
+        true => Ok(()),
+        false => Err(String::from("too big")),
+      }
+    });
+  }
+
+  // A list with a sum over 30 shrinks until the sum is exactly 31, where dropping or
+  // shrinking any number would fix it
+  #[test]
+  fn test_shrinks() {
+    let message = failure(|| {
+      let strategy = Vecs {
+        item: Ints { low: 0, high: 20 },
+        min: 0,
+        max: 10,
+      };
+      check(&strategy, |numbers| {
+        let sum: i64 = numbers.iter().sum();
+        match sum <= 30 {
+          true => Ok(()),
+          false => Err(format!("sum {}", sum)),
+        }
+      })
+    });
+    assert!(
+      message.contains(": sum 31\n  minimal value: ["),
+      "{}",
+      message
+    );
+  }
+
+  #[test]
+  fn test_shrink_candidates() {
+    let ints = Ints { low: -5, high: 5 };
+    assert_eq!(vec![-5, -1, 2], ints.shrink(&3));
+    assert!(ints.shrink(&-5).is_empty());
+    assert_eq!(vec!['a', 'b'], OneOf(vec!['a', 'b', 'c']).shrink(&'c'));
+    let pairs = (Ints { low: 0, high: 9 }, Ints { low: 0, high: 9 });
+    assert_eq!(vec![(0, 1), (1, 0)], pairs.shrink(&(1, 1)));
+  }
+}
@@ -0,0 +1,80 @@
+// A small seeded random number generator (SplitMix64) for generating puzzle inputs, so
+// that the same seed always gives the same input on every platform.
+
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+  pub fn new(seed: u64) -> Rng {
+    Rng(seed)
+  }
+
+  pub fn next_u64(&mut self) -> u64 {
+    self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = self.0;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+  }
+
+  // A number from 0 to `n - 1`, or 0 if `n` is 0
+  pub fn below(&mut self, n: u64) -> u64 {
+    match n {
+      0 => 0,
+      n => self.next_u64() % n,
+    }
+  }
+
+  // A number from `low` to `high` inclusive
+  pub fn range(&mut self, low: i64, high: i64) -> i64 {
+    low + self.below((high - low) as u64 + 1) as i64
+  }
+
+  // True once in every `n` calls, on average
+  pub fn one_in(&mut self, n: u64) -> bool {
+    self.below(n) == 0
+  }
+
+  pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+    &items[self.below(items.len() as u64) as usize]
+  }
+
+  pub fn shuffle<T>(&mut self, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+      items.swap(i, self.below(i as u64 + 1) as usize);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_seeded() {
+    // SplitMix64's published first output for seed 0
+    assert_eq!(0xe220_a839_7b1d_cdaf, Rng::new(0).next_u64());
+    let (mut a, mut b) = (Rng::new(7), Rng::new(7));
+    for _ in 0..3 {
+      assert_eq!(a.next_u64(), b.next_u64());
+    }
+    assert_ne!(Rng::new(7).next_u64(), Rng::new(8).next_u64());
+  }
+
+  #[test]
+  fn test_range() {
+    let mut rng = Rng::new(1);
+    for _ in 0..1000 {
+      let n = rng.range(-3, 3);
+      assert!((-3..=3).contains(&n), "{}", n);
+      assert!(rng.below(5) < 5);
+    }
+    assert_eq!(0, rng.below(0));
+    assert_eq!(4, rng.range(4, 4));
+
+    let mut items = vec![1, 2, 3, 4, 5];
+    rng.shuffle(&mut items);
+    items.sort();
+    assert_eq!(vec![1, 2, 3, 4, 5], items);
+  }
+}
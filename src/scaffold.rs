@@ -0,0 +1,244 @@
+// `new <day>`: generates `src/dayN.rs` from a template, registers it in lib.rs and
+// creates an empty input file and example, refusing to touch anything that already
+// exists.
+
+use crate::AocError;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// The module for a new day, with part 1 unsolved
+pub fn template(day: u32) -> String {
+  format!(
+    r#"use crate::input::Input;
+use crate::{{Answer, AocError, Day}};
+
+fn parse_input(input: &Input) -> Result<Vec<String>, AocError> {{
+  Ok(input.lines().map(String::from).collect())
+}}
+
+pub fn parse(input: &str) -> Result<Vec<String>, AocError> {{
+  parse_input(&Input::new(input))
+}}
+
+pub fn solve_part1(_lines: &[String]) -> Result<i64, AocError> {{
+  Err(AocError::NoSolution(String::from("part 1 isn't solved yet")))
+}}
+
+pub struct Day{day};
+
+impl Day for Day{day} {{
+  type Model = Vec<String>;
+  const SOURCE: &'static str = include_str!("day{day}.rs");
+
+  fn day(&self) -> u32 {{
+    {day}
+  }}
+
+  fn title(&self) -> &'static str {{
+    "Day {day}"
+  }}
+
+  fn parts(&self) -> &'static [u32] {{
+    &[1]
+  }}
+
+  fn parse(&self, input: &Input) -> Result<Self::Model, AocError> {{
+    parse_input(input)
+  }}
+
+  fn part1(&self, model: &Self::Model) -> Result<Answer, AocError> {{
+    Ok(solve_part1(model)?.into())
+  }}
+}}
+"#,
+    day = day
+  )
+}
+
+// The first example for a new day, which fails the example test until the puzzle's
+// example and its answer are filled in
+pub const EXAMPLE: &str =
+  "# TODO: the example's answer here, and the example itself after the line below
+part1 = \"\"
+---
+";
+
+// Adds `pub mod dayN;` and `&dayN::DayN` to the text of lib.rs, keeping both lists sorted
+// the way rustfmt and `SOLUTIONS` expect
+pub fn register(lib: &str, day: u32) -> Result<String, AocError> {
+  let name = format!("day{}", day);
+  let mut lines: Vec<String> = lib.lines().map(String::from).collect();
+
+  let module = |line: &str| {
+    let line = line.strip_prefix("pub ").unwrap_or(line);
+    line
+      .strip_prefix("mod ")
+      .and_then(|rest| rest.strip_suffix(';'))
+      .map(String::from)
+  };
+  let modules: Vec<(usize, String)> = lines
+    .iter()
+    .enumerate()
+    .filter_map(|(i, line)| module(line).map(|m| (i, m)))
+    .collect();
+  if modules.iter().any(|(_, m)| *m == name) {
+    return Err(AocError::InvalidArgument(format!(
+      "day {} is already registered in lib.rs",
+      day
+    )));
+  }
+  let index = match modules.iter().find(|(_, m)| *m > name) {
+    Some(&(i, _)) => i,
+    None => match modules.last() {
+      Some(&(i, _)) => i + 1,
+      None => return Err(missing("mod declarations")),
+    },
+  };
+  lines.insert(index, format!("pub mod {};", name));
+
+  let start = lines
+    .iter()
+    .position(|line| line.starts_with("static SOLUTIONS"))
+    .ok_or_else(|| missing("the SOLUTIONS list"))?;
+  let end = start
+    + lines[start..]
+      .iter()
+      .position(|line| line.trim() == "];")
+      .ok_or_else(|| missing("the end of the SOLUTIONS list"))?;
+  let registered = |line: &str| -> Option<u32> {
+    line
+      .trim()
+      .strip_prefix("&day")?
+      .split("::")
+      .next()?
+      .parse()
+      .ok()
+  };
+  let index = (start + 1..end)
+    .find(|&i| registered(&lines[i]).is_some_and(|d| d > day))
+    .unwrap_or(end);
+  lines.insert(index, format!("  &{}::Day{},", name, day));
+
+  let mut text = lines.join("\n");
+  text.push('\n');
+  Ok(text)
+}
+
+fn missing(what: &str) -> AocError {
+  AocError::InvalidArgument(format!("couldn't find {} in lib.rs", what))
+}
+
+// Creates the module and input file for `day` under `root` and registers the module,
+// returning the paths that were written. Nothing is written if any of them exist.
+pub fn create(root: &Path, input: &Path, day: u32) -> Result<Vec<PathBuf>, AocError> {
+  if !(1..=25).contains(&day) {
+    return Err(AocError::InvalidArgument(format!(
+      "expected a day from 1 to 25, found {}",
+      day
+    )));
+  }
+  let module = root.join("src").join(format!("day{}.rs", day));
+  let lib_path = root.join("src").join("lib.rs");
+  let example = root.join(format!("examples/day{}/1.txt", day));
+  for path in &[&module, input, &example] {
+    if path.exists() {
+      return Err(AocError::InvalidArgument(format!(
+        "{} already exists",
+        path.display()
+      )));
+    }
+  }
+  let lib = fs::read_to_string(&lib_path).map_err(|e| io(&lib_path, e))?;
+  let lib = register(&lib, day)?;
+
+  fs::write(&module, template(day)).map_err(|e| io(&module, e))?;
+  if let Some(dir) = input.parent() {
+    fs::create_dir_all(dir).map_err(|e| io(dir, e))?;
+  }
+  fs::write(input, "").map_err(|e| io(input, e))?;
+  if let Some(dir) = example.parent() {
+    fs::create_dir_all(dir).map_err(|e| io(dir, e))?;
+  }
+  fs::write(&example, EXAMPLE).map_err(|e| io(&example, e))?;
+  fs::write(&lib_path, lib).map_err(|e| io(&lib_path, e))?;
+  Ok(vec![module, lib_path, input.to_path_buf(), example])
+}
+
+fn io(path: &Path, err: std::io::Error) -> AocError {
+  AocError::Io(format!("{}: {}", path.display(), err))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::env;
+  use std::process;
+
+  static LIB: &str = "mod answer;
+pub mod cli;
+pub mod day1;
+pub mod day14;
+pub mod day2;
+mod solution;
+
+static SOLUTIONS: &[&dyn Solution] = &[
+  &day1::Day1,
+  &day2::Day2,
+  &day14::Day14,
+];
+";
+
+  #[test]
+  fn test_register() {
+    let lib = register(LIB, 15).unwrap();
+    assert!(lib.contains("pub mod day14;\npub mod day15;\npub mod day2;\n"));
+    assert!(lib.contains("  &day14::Day14,\n  &day15::Day15,\n];\n"));
+
+    let lib = register(&lib, 3).unwrap();
+    assert!(lib.contains("pub mod day2;\npub mod day3;\nmod solution;\n"));
+    assert!(lib.contains("  &day2::Day2,\n  &day3::Day3,\n  &day14::Day14,\n"));
+
+    let lib = register(&lib, 25).unwrap();
+    assert!(lib.contains("pub mod day2;\npub mod day25;\npub mod day3;\n"));
+    assert!(lib.contains("  &day15::Day15,\n  &day25::Day25,\n];\n"));
+
+    assert!(register(&lib, 15).is_err());
+    assert!(register("fn main() {}\n", 15).is_err());
+  }
+
+  #[test]
+  fn test_create() {
+    let root = env::temp_dir().join(format!("aoc2020-scaffold-{}", process::id()));
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("src/lib.rs"), LIB).unwrap();
+    let input = root.join("input/day15.txt");
+
+    let written = create(&root, &input, 15).unwrap();
+    assert_eq!(4, written.len());
+    assert_eq!(
+      template(15),
+      fs::read_to_string(root.join("src/day15.rs")).unwrap()
+    );
+    assert_eq!("", fs::read_to_string(&input).unwrap());
+    let example = crate::examples::Example::parse(
+      15,
+      "1",
+      &fs::read_to_string(root.join("examples/day15/1.txt")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(vec![(1, String::new())], example.answers);
+    let lib = fs::read_to_string(root.join("src/lib.rs")).unwrap();
+    assert!(lib.contains("pub mod day15;") && lib.contains("&day15::Day15,"));
+
+    // Refuses to overwrite, and leaves lib.rs alone
+    assert!(create(&root, &input, 15).is_err());
+    fs::remove_file(&input).unwrap();
+    assert!(create(&root, &input, 15).is_err());
+    fs::remove_file(root.join("src/day15.rs")).unwrap();
+    assert!(create(&root, &input, 15).is_err());
+    assert_eq!(lib, fs::read_to_string(root.join("src/lib.rs")).unwrap());
+
+    assert!(create(&root, &root.join("input/day26.txt"), 26).is_err());
+    fs::remove_dir_all(&root).unwrap();
+  }
+}
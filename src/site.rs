@@ -0,0 +1,426 @@
+// The Advent of Code website: downloading inputs for `fetch` and posting answers for
+// `submit`. Requests go to `base_url`, which defaults to the real site but can point at
+// a local stub server.
+
+use crate::config::Config;
+use crate::AocError;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+// The site asks automated tools to say who they are
+const USER_AGENT: &str = "github.com/mike-lawson/aoc2020 by mike@mjlawson.net";
+
+pub struct Site {
+  base_url: String,
+  session: Option<String>,
+  agent: ureq::Agent,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Fetched {
+  Downloaded,
+  // The input was already there, so nothing was downloaded
+  Cached,
+}
+
+// What the site said about a submitted answer
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+  Right,
+  Wrong,
+  TooHigh,
+  TooLow,
+  // With how long to wait, if the site said
+  RateLimited(Option<String>),
+  // The part has already been solved, so the answer wasn't checked
+  AlreadySolved,
+  // Anything else, with the text of the response
+  Unknown(String),
+}
+
+impl Outcome {
+  // Reads the outcome from the text of the site's response page
+  pub fn parse(page: &str) -> Outcome {
+    let text = article_text(page);
+    if text.contains("That's the right answer") {
+      Outcome::Right
+    } else if text.contains("That's not the right answer") {
+      if text.contains("your answer is too high") {
+        Outcome::TooHigh
+      } else if text.contains("your answer is too low") {
+        Outcome::TooLow
+      } else {
+        Outcome::Wrong
+      }
+    } else if text.contains("You gave an answer too recently") {
+      let wait = text
+        .split("You have ")
+        .nth(1)
+        .and_then(|rest| rest.split(" left to wait").next())
+        .map(String::from);
+      Outcome::RateLimited(wait)
+    } else if text.contains("You don't seem to be solving the right level") {
+      Outcome::AlreadySolved
+    } else {
+      Outcome::Unknown(text)
+    }
+  }
+
+  // The name used in the submission history, for outcomes worth remembering
+  pub fn label(&self) -> Option<&'static str> {
+    match self {
+      Outcome::Right => Some("right"),
+      Outcome::Wrong => Some("wrong"),
+      Outcome::TooHigh => Some("too high"),
+      Outcome::TooLow => Some("too low"),
+      _ => None,
+    }
+  }
+
+  pub fn from_label(label: &str) -> Option<Outcome> {
+    match label {
+      "right" => Some(Outcome::Right),
+      "wrong" => Some(Outcome::Wrong),
+      "too high" => Some(Outcome::TooHigh),
+      "too low" => Some(Outcome::TooLow),
+      _ => None,
+    }
+  }
+}
+
+impl fmt::Display for Outcome {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Outcome::Right => write!(f, "that's the right answer"),
+      Outcome::Wrong => write!(f, "that's not the right answer"),
+      Outcome::TooHigh => write!(f, "that's not the right answer: it's too high"),
+      Outcome::TooLow => write!(f, "that's not the right answer: it's too low"),
+      Outcome::RateLimited(Some(wait)) => write!(f, "answered too recently, wait {}", wait),
+      Outcome::RateLimited(None) => write!(f, "answered too recently, wait a while"),
+      Outcome::AlreadySolved => write!(f, "this part has already been solved"),
+      Outcome::Unknown(text) => write!(f, "unrecognised response: {}", text),
+    }
+  }
+}
+
+// The text of the page's <article>, or the whole page, without tags and with runs of
+// whitespace collapsed
+fn article_text(page: &str) -> String {
+  let article = page
+    .split("<article")
+    .nth(1)
+    .and_then(|rest| rest.split("</article>").next())
+    .map(|article| article.split_once('>').map_or("", |(_, rest)| rest))
+    .unwrap_or(page);
+  let mut text = String::new();
+  let mut in_tag = false;
+  for c in article.chars() {
+    match c {
+      '<' => in_tag = true,
+      '>' => in_tag = false,
+      c if !in_tag => text.push(c),
+      _ => {}
+    }
+  }
+  text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+impl Site {
+  pub fn new(base_url: &str, session: Option<String>) -> Site {
+    let agent = ureq::AgentBuilder::new()
+      .timeout(Duration::from_secs(30))
+      .user_agent(USER_AGENT)
+      .build();
+    Site {
+      base_url: String::from(base_url.trim_end_matches('/')),
+      session,
+      agent,
+    }
+  }
+
+  // `AOC_SESSION` and `AOC_BASE_URL` win over the config file's `session` and `base_url`
+  pub fn from_config(config: &Config) -> Site {
+    let base_url = env::var("AOC_BASE_URL")
+      .ok()
+      .or_else(|| config.base_url.clone())
+      .unwrap_or_else(|| String::from(DEFAULT_BASE_URL));
+    let session = env::var("AOC_SESSION")
+      .ok()
+      .or_else(|| config.session.clone());
+    Site::new(&base_url, session)
+  }
+
+  fn session(&self) -> Result<&str, AocError> {
+    match self.session.as_deref().map(str::trim) {
+      Some(session) if !session.is_empty() => Ok(session),
+      _ => Err(AocError::InvalidArgument(String::from(
+        "no session token: set AOC_SESSION, or session in aoc.toml, to the session \
+         cookie from the puzzle site",
+      ))),
+    }
+  }
+
+  // Downloads the puzzle input for `day`
+  pub fn input(&self, day: u32) -> Result<String, AocError> {
+    let url = format!("{}/2020/day/{}/input", self.base_url, day);
+    let response = self
+      .agent
+      .get(&url)
+      .set("Cookie", &format!("session={}", self.session()?))
+      .call();
+    let response = match response {
+      Ok(response) => response,
+      Err(ureq::Error::Status(404, _)) => {
+        return Err(AocError::Http(format!(
+          "{}: day {}'s input isn't available yet",
+          url, day
+        )))
+      }
+      Err(ureq::Error::Status(status, response)) => {
+        let body = response.into_string().unwrap_or_default();
+        let reason = body.lines().next().unwrap_or("").trim();
+        return Err(AocError::Http(format!("{}: {} {}", url, status, reason)));
+      }
+      Err(err) => return Err(AocError::Http(format!("{}: {}", url, err))),
+    };
+    let mut body = String::new();
+    response
+      .into_reader()
+      .read_to_string(&mut body)
+      .map_err(|e| AocError::Http(format!("{}: {}", url, e)))?;
+    if body.trim().is_empty() {
+      return Err(AocError::Http(format!("{}: the input was empty", url)));
+    }
+    Ok(body)
+  }
+
+  // Posts `answer` for `day` and `part`, returning what the site made of it
+  pub fn submit(&self, day: u32, part: u32, answer: &str) -> Result<Outcome, AocError> {
+    let url = format!("{}/2020/day/{}/answer", self.base_url, day);
+    let response = self
+      .agent
+      .post(&url)
+      .set("Cookie", &format!("session={}", self.session()?))
+      .send_form(&[("level", &part.to_string()), ("answer", answer)]);
+    let page = match response {
+      Ok(response) => response.into_string(),
+      Err(ureq::Error::Status(status, _)) => {
+        return Err(AocError::Http(format!("{}: {}", url, status)))
+      }
+      Err(err) => return Err(AocError::Http(format!("{}: {}", url, err))),
+    };
+    let page = page.map_err(|e| AocError::Http(format!("{}: {}", url, e)))?;
+    Ok(Outcome::parse(&page))
+  }
+
+  // Downloads `day`'s input to `path`, unless it's already there. An empty file, like
+  // the one `new` creates, doesn't count.
+  pub fn fetch(&self, day: u32, path: &Path) -> Result<Fetched, AocError> {
+    let io = |e: std::io::Error| AocError::Io(format!("{}: {}", path.display(), e));
+    match fs::metadata(path) {
+      Ok(metadata) if metadata.len() > 0 => return Ok(Fetched::Cached),
+      _ => {}
+    }
+    let input = self.input(day)?;
+    if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir).map_err(io)?;
+    }
+    // Write to the side first, so an interrupted download never looks cached
+    let partial = path.with_extension("txt.partial");
+    fs::write(&partial, input).map_err(io)?;
+    fs::rename(&partial, path).map_err(io)?;
+    Ok(Fetched::Downloaded)
+  }
+}
+
+#[cfg(test)]
+pub(crate) mod stub {
+  // A one-thread HTTP server for tests, which answers each request with the next of
+  // `responses` and records the requests it saw
+
+  use std::io::{BufRead, BufReader, Write};
+  use std::net::TcpListener;
+  use std::sync::{Arc, Mutex};
+  use std::thread;
+
+  pub struct Stub {
+    pub url: String,
+    pub requests: Arc<Mutex<Vec<String>>>,
+  }
+
+  pub fn serve(responses: Vec<(u16, &'static str)>) -> Stub {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&requests);
+    thread::spawn(move || {
+      for (status, body) in responses {
+        let (stream, _) = match listener.accept() {
+          Ok(connection) => connection,
+          Err(_) => return,
+        };
+        let mut reader = BufReader::new(stream);
+        let mut request = String::new();
+        let mut length = 0;
+        loop {
+          let mut line = String::new();
+          if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+            break;
+          }
+          if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+            length = value.trim().parse().unwrap_or(0);
+          }
+          request.push_str(&line);
+        }
+        let mut body_in = vec![0; length];
+        let _ = std::io::Read::read_exact(&mut reader, &mut body_in);
+        request.push_str("\r\n");
+        request.push_str(&String::from_utf8_lossy(&body_in));
+        seen.lock().unwrap().push(request);
+
+        let mut stream = reader.into_inner();
+        let _ = write!(
+          stream,
+          "HTTP/1.1 {} Stub\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+          status,
+          body.len(),
+          body
+        );
+      }
+    });
+    Stub { url, requests }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::process;
+
+  fn temp_file(name: &str) -> std::path::PathBuf {
+    let dir = env::temp_dir().join(format!("aoc2020-site-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir.join("day1.txt")
+  }
+
+  #[test]
+  fn test_fetch_caches() {
+    let stub = stub::serve(vec![(200, "1721\n979\n")]);
+    let site = Site::new(&format!("{}/", stub.url), Some(String::from("abc123")));
+    let path = temp_file("fetch");
+
+    assert_eq!(Ok(Fetched::Downloaded), site.fetch(1, &path));
+    assert_eq!("1721\n979\n", fs::read_to_string(&path).unwrap());
+    // The stub only answers once, so this would fail if it asked again
+    assert_eq!(Ok(Fetched::Cached), site.fetch(1, &path));
+
+    let requests = stub.requests.lock().unwrap();
+    assert_eq!(1, requests.len());
+    assert!(requests[0].starts_with("GET /2020/day/1/input HTTP/1.1\r\n"));
+    assert!(requests[0].contains("session=abc123"), "{}", requests[0]);
+    assert!(requests[0].contains(USER_AGENT), "{}", requests[0]);
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+  }
+
+  #[test]
+  fn test_fetch_replaces_empty_file() {
+    let stub = stub::serve(vec![(200, "1721\n")]);
+    let site = Site::new(&stub.url, Some(String::from("abc123")));
+    let path = temp_file("empty");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "").unwrap();
+
+    assert_eq!(Ok(Fetched::Downloaded), site.fetch(1, &path));
+    assert_eq!("1721\n", fs::read_to_string(&path).unwrap());
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+  }
+
+  fn page(article: &str) -> String {
+    format!(
+      "<html><body><main>\n<article><p>{}</p></article>\n</main></body></html>",
+      article
+    )
+  }
+
+  #[test]
+  fn test_outcome() {
+    let cases = [
+      ("That's the right answer!  You are <em>one gold star</em> closer.", Outcome::Right),
+      ("That's not the right answer.  If you're stuck, make sure you're using the full input data.", Outcome::Wrong),
+      ("That's not the right answer; your answer is too high.  <a href=\"/2020/day/1\">[Return]</a>", Outcome::TooHigh),
+      ("That's not the right answer; your answer is too low.", Outcome::TooLow),
+      ("You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 34s left to wait.", Outcome::RateLimited(Some(String::from("34s")))),
+      ("You gave an answer too recently.", Outcome::RateLimited(None)),
+      ("You don't seem to be solving the right level.  Did you already complete it?", Outcome::AlreadySolved),
+      ("Something\n  <b>new</b>", Outcome::Unknown(String::from("Something new"))),
+    ];
+    for (article, outcome) in cases.iter() {
+      assert_eq!(*outcome, Outcome::parse(&page(article)), "{}", article);
+      if let Some(label) = outcome.label() {
+        assert_eq!(Some(outcome.clone()), Outcome::from_label(label));
+      }
+    }
+  }
+
+  #[test]
+  fn test_submit() {
+    let stub = stub::serve(vec![
+      (
+        200,
+        "<article><p>That's not the right answer; your answer is too low.</p></article>",
+      ),
+      (500, "oops"),
+    ]);
+    let site = Site::new(&stub.url, Some(String::from("abc123")));
+    assert_eq!(Ok(Outcome::TooLow), site.submit(1, 2, "12 34"));
+    assert!(matches!(site.submit(1, 2, "12"), Err(AocError::Http(_))));
+
+    let requests = stub.requests.lock().unwrap();
+    assert!(requests[0].starts_with("POST /2020/day/1/answer HTTP/1.1\r\n"));
+    assert!(requests[0].contains("session=abc123"));
+    assert!(
+      requests[0].ends_with("\r\n\r\nlevel=2&answer=12+34"),
+      "{}",
+      requests[0]
+    );
+  }
+
+  #[test]
+  fn test_fetch_errors() {
+    let stub = stub::serve(vec![
+      (404, "Not found"),
+      (
+        400,
+        "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n",
+      ),
+    ]);
+    let site = Site::new(&stub.url, Some(String::from("abc123")));
+    let path = temp_file("errors");
+
+    match site.fetch(25, &path) {
+      Err(AocError::Http(message)) => {
+        assert!(message.contains("isn't available yet"), "{}", message)
+      }
+      other => panic!("expected an HTTP error, got {:?}", other),
+    }
+    match site.fetch(1, &path) {
+      Err(AocError::Http(message)) => {
+        assert!(message.contains("400 Puzzle inputs differ"), "{}", message)
+      }
+      other => panic!("expected an HTTP error, got {:?}", other),
+    }
+    assert!(!path.exists());
+
+    let site = Site::new(&stub.url, None);
+    assert!(matches!(
+      site.fetch(1, &path),
+      Err(AocError::InvalidArgument(_))
+    ));
+  }
+}
@@ -0,0 +1,169 @@
+use crate::generate::Params;
+use crate::input::Input;
+use crate::rng::Rng;
+use crate::trace;
+use crate::{Answer, AocError};
+use std::any::Any;
+
+// The shared code that every solver's answers pass through, which counts as part of
+// each solver's version
+const SHARED_SOURCES: &[&str] = &[
+  include_str!("answer.rs"),
+  include_str!("error.rs"),
+  include_str!("input.rs"),
+  include_str!("solution.rs"),
+];
+
+// Another way of solving one of a day's parts, e.g. a slow but obviously correct one,
+// which `diff-test` checks against the day's own `part1` / `part2`
+pub struct Alternative<M> {
+  pub part: u32,
+  pub name: &'static str,
+  pub solve: fn(&M) -> Result<Answer, AocError>,
+}
+
+// A single day's puzzle. Each `dayN` module implements this on a unit struct, and
+// registers that struct in `SOLUTIONS` in lib.rs. The input is parsed once into a
+// `Model`, which every part is then solved from.
+pub trait Day: Sync {
+  type Model: Any + Send + Sync;
+
+  // The module's own source, `include_str!("dayN.rs")`. Cached answers are only
+  // reused while it stays the same.
+  const SOURCE: &'static str;
+
+  fn day(&self) -> u32;
+
+  fn title(&self) -> &'static str;
+
+  // The parts that have been solved so far
+  fn parts(&self) -> &'static [u32] {
+    &[1, 2]
+  }
+
+  fn parse(&self, input: &Input) -> Result<Self::Model, AocError>;
+
+  fn part1(&self, model: &Self::Model) -> Result<Answer, AocError>;
+
+  fn part2(&self, _model: &Self::Model) -> Result<Answer, AocError> {
+    Err(AocError::PartNotImplemented(self.day(), 2))
+  }
+
+  fn alternatives(&self) -> Vec<Alternative<Self::Model>> {
+    Vec::new()
+  }
+
+  // A random puzzle input with a solution, for days that can make them
+  fn generate(&self, _rng: &mut Rng, _params: &Params) -> Option<String> {
+    None
+  }
+}
+
+// Any day's parsed input
+pub type Model = Box<dyn Any + Send + Sync>;
+
+// A `Day` without its model type, so that days can be kept in one registry
+pub trait Solution: Sync {
+  fn day(&self) -> u32;
+
+  fn title(&self) -> &'static str;
+
+  fn parts(&self) -> &'static [u32];
+
+  // A hash of the solver's source and the shared modules it runs through, which changes
+  // whenever either is edited
+  fn version(&self) -> String;
+
+  fn parse(&self, input: &Input) -> Result<Model, AocError>;
+
+  // Solves `part` from a model returned by this solution's `parse`
+  fn solve_parsed(&self, part: u32, model: &Model) -> Result<Answer, AocError>;
+
+  // The names of the other implementations of `part`
+  fn alternatives(&self, part: u32) -> Vec<&'static str>;
+
+  // Solves `part` with the other implementation called `name`
+  fn solve_alternative(&self, part: u32, name: &str, model: &Model) -> Result<Answer, AocError>;
+
+  fn generate(&self, rng: &mut Rng, params: &Params) -> Option<String>;
+
+  fn solve(&self, part: u32, input: &Input) -> Result<Answer, AocError> {
+    if !self.parts().contains(&part) {
+      return Err(AocError::PartNotImplemented(self.day(), part));
+    }
+    self.solve_parsed(part, &self.parse(input)?)
+  }
+}
+
+impl<D: Day> Solution for D {
+  fn day(&self) -> u32 {
+    Day::day(self)
+  }
+
+  fn title(&self) -> &'static str {
+    Day::title(self)
+  }
+
+  fn parts(&self) -> &'static [u32] {
+    Day::parts(self)
+  }
+
+  fn version(&self) -> String {
+    let mut source = String::from(D::SOURCE);
+    source.extend(SHARED_SOURCES.iter().copied());
+    Input::new(&source).hash()
+  }
+
+  fn parse(&self, input: &Input) -> Result<Model, AocError> {
+    let _span = trace::phase(Day::day(self), None);
+    Ok(Box::new(Day::parse(self, input)?))
+  }
+
+  fn solve_parsed(&self, part: u32, model: &Model) -> Result<Answer, AocError> {
+    if !Day::parts(self).contains(&part) {
+      return Err(AocError::PartNotImplemented(Day::day(self), part));
+    }
+    let model = downcast(self, model)?;
+    let _span = trace::phase(Day::day(self), Some(part));
+    match part {
+      1 => self.part1(model),
+      _ => self.part2(model),
+    }
+  }
+
+  fn alternatives(&self, part: u32) -> Vec<&'static str> {
+    Day::alternatives(self)
+      .iter()
+      .filter(|alternative| alternative.part == part)
+      .map(|alternative| alternative.name)
+      .collect()
+  }
+
+  fn solve_alternative(&self, part: u32, name: &str, model: &Model) -> Result<Answer, AocError> {
+    let alternative = Day::alternatives(self)
+      .into_iter()
+      .find(|alternative| alternative.part == part && alternative.name == name)
+      .ok_or_else(|| {
+        AocError::InvalidArgument(format!(
+          "day {} part {} has no implementation called {:?}",
+          Day::day(self),
+          part,
+          name
+        ))
+      })?;
+    (alternative.solve)(downcast(self, model)?)
+  }
+
+  fn generate(&self, rng: &mut Rng, params: &Params) -> Option<String> {
+    Day::generate(self, rng, params)
+  }
+}
+
+fn downcast<'a, D: Day>(day: &D, model: &'a Model) -> Result<&'a D::Model, AocError> {
+  model.downcast_ref::<D::Model>().ok_or_else(|| {
+    AocError::InvalidArgument(format!(
+      "the model given to day {} came from another day",
+      Day::day(day)
+    ))
+  })
+}
@@ -0,0 +1,106 @@
+use crate::config::{Config, ROOT};
+use crate::AocError;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// Where puzzle inputs are read from. This is the only place that decides it.
+#[derive(Debug)]
+pub enum InputSource {
+  // `dayN.txt` inside this directory
+  Dir(PathBuf),
+  // The same file, whatever the day
+  File(PathBuf),
+  // Standard input, read once and shared by every part that asks
+  Stdin(OnceLock<String>),
+}
+
+impl InputSource {
+  // `--input` wins (with `-` meaning stdin), then `AOC_INPUT_DIR`, then the config
+  // file's `input_dir`, then the repository's own `input/` directory.
+  pub fn resolve(input: Option<&str>, config: &Config) -> InputSource {
+    InputSource::resolve_with(input, env::var_os("AOC_INPUT_DIR"), config)
+  }
+
+  fn resolve_with(input: Option<&str>, env_dir: Option<OsString>, config: &Config) -> InputSource {
+    match (input, env_dir, &config.input_dir) {
+      (Some("-"), _, _) => InputSource::Stdin(OnceLock::new()),
+      (Some(path), _, _) => InputSource::File(PathBuf::from(path)),
+      (None, Some(dir), _) => InputSource::Dir(PathBuf::from(dir)),
+      (None, None, Some(dir)) => InputSource::Dir(dir.clone()),
+      (None, None, None) => InputSource::Dir(Path::new(ROOT).join("input")),
+    }
+  }
+
+  // A human-readable description of where `day`'s input comes from
+  pub fn describe(&self, day: u32) -> String {
+    match self.path(day) {
+      Some(path) => path.display().to_string(),
+      None => String::from("<stdin>"),
+    }
+  }
+
+  // The file `day`'s input is read from, unless it comes from stdin
+  pub fn path(&self, day: u32) -> Option<PathBuf> {
+    match self {
+      InputSource::Dir(dir) => Some(dir.join(format!("day{}.txt", day))),
+      InputSource::File(path) => Some(path.clone()),
+      InputSource::Stdin(_) => None,
+    }
+  }
+
+  pub fn read(&self, day: u32) -> Result<String, AocError> {
+    let path = match self {
+      InputSource::Dir(_) | InputSource::File(_) => self.path(day).unwrap_or_default(),
+      InputSource::Stdin(cell) => {
+        if let Some(input) = cell.get() {
+          return Ok(input.clone());
+        }
+        let mut input = String::new();
+        io::stdin()
+          .read_to_string(&mut input)
+          .map_err(|e| AocError::Io(format!("<stdin>: {}", e)))?;
+        return Ok(cell.get_or_init(|| input).clone());
+      }
+    };
+    fs::read_to_string(&path).map_err(|e| AocError::Io(format!("{}: {}", path.display(), e)))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_precedence() {
+    let config = Config {
+      input_dir: Some(PathBuf::from("/from/config")),
+      ..Config::default()
+    };
+    let env_dir = || Some(OsString::from("/from/env"));
+
+    let source = InputSource::resolve_with(Some("alt.txt"), env_dir(), &config);
+    assert_eq!("alt.txt", source.describe(3));
+    let source = InputSource::resolve_with(Some("-"), env_dir(), &config);
+    assert_eq!("<stdin>", source.describe(3));
+    let source = InputSource::resolve_with(None, env_dir(), &config);
+    assert_eq!("/from/env/day3.txt", source.describe(3));
+    let source = InputSource::resolve_with(None, None, &config);
+    assert_eq!("/from/config/day3.txt", source.describe(3));
+    let source = InputSource::resolve_with(None, None, &Config::default());
+    assert_eq!(format!("{}/input/day3.txt", ROOT), source.describe(3));
+  }
+
+  #[test]
+  fn test_read() {
+    let source = InputSource::resolve_with(None, None, &Config::default());
+    assert!(source.read(12).unwrap().starts_with("W2\n"));
+    match source.read(99) {
+      Err(AocError::Io(message)) => assert!(message.contains("day99.txt")),
+      other => panic!("expected an io error, got {:?}", other),
+    }
+  }
+}
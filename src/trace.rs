@@ -0,0 +1,377 @@
+// Optional span instrumentation, for seeing where the time goes inside a solver. Built
+// with `--features trace`, every `span` records how long it was open, which spans it ran
+// inside and any `count`s made while it was the innermost one. Without the feature
+// spans and counts compile to nothing and `take` is always empty.
+//
+// The spans recorded so far can be written as Chrome trace JSON (for chrome://tracing
+// or Perfetto) or as folded stacks (for flamegraph tools).
+
+use crate::json::Value;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+// Whether spans are being recorded in this build
+pub const ENABLED: bool = cfg!(feature = "trace");
+
+// One closed span
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+  pub name: String,
+  // The names of the spans it ran inside and its own, outermost first, joined by ';'
+  pub path: String,
+  pub thread: u64,
+  // Since the first span opened
+  pub start: Duration,
+  pub duration: Duration,
+  // The time not spent in nested spans
+  pub own: Duration,
+  pub counts: Vec<(&'static str, u64)>,
+}
+
+// Open while this guard is alive
+#[must_use]
+pub struct Span(());
+
+// Opens a span inside whatever span is open on this thread
+#[inline]
+pub fn span(name: &'static str) -> Span {
+  #[cfg(feature = "trace")]
+  record::open(String::from(name));
+  let _ = name;
+  Span(())
+}
+
+// A span for parsing a day's input (`part` None) or solving one of its parts
+#[inline]
+pub fn phase(day: u32, part: Option<u32>) -> Span {
+  #[cfg(feature = "trace")]
+  record::open(match part {
+    Some(part) => format!("day{}::part{}", day, part),
+    None => format!("day{}::parse", day),
+  });
+  let _ = (day, part);
+  Span(())
+}
+
+// Adds one to the counter `name` of the innermost open span on this thread, e.g. once
+// per loop iteration
+#[inline]
+pub fn count(name: &'static str) {
+  #[cfg(feature = "trace")]
+  record::count(name);
+  let _ = name;
+}
+
+impl Drop for Span {
+  #[inline]
+  fn drop(&mut self) {
+    #[cfg(feature = "trace")]
+    record::close();
+  }
+}
+
+// Removes and returns every span closed so far, on any thread
+pub fn take() -> Trace {
+  #[cfg(feature = "trace")]
+  let events = record::take();
+  #[cfg(not(feature = "trace"))]
+  let events = Vec::new();
+  Trace(events)
+}
+
+#[cfg(feature = "trace")]
+mod record {
+  use super::Event;
+  use lazy_static::lazy_static;
+  use std::cell::{Cell, RefCell};
+  use std::mem;
+  use std::sync::atomic::{AtomicU64, Ordering};
+  use std::sync::Mutex;
+  use std::time::{Duration, Instant};
+
+  struct Frame {
+    name: String,
+    path: String,
+    start: Instant,
+    nested: Duration,
+    counts: Vec<(&'static str, u64)>,
+  }
+
+  lazy_static! {
+    static ref EPOCH: Instant = Instant::now();
+    static ref EVENTS: Mutex<Vec<Event>> = Mutex::new(Vec::new());
+  }
+
+  static THREADS: AtomicU64 = AtomicU64::new(1);
+
+  thread_local! {
+    static STACK: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+    static THREAD: Cell<u64> = const { Cell::new(0) };
+  }
+
+  fn thread() -> u64 {
+    THREAD.with(|thread| {
+      if thread.get() == 0 {
+        thread.set(THREADS.fetch_add(1, Ordering::Relaxed));
+      }
+      thread.get()
+    })
+  }
+
+  pub fn open(name: String) {
+    lazy_static::initialize(&EPOCH);
+    STACK.with(|stack| {
+      let mut stack = stack.borrow_mut();
+      let path = match stack.last() {
+        Some(parent) => format!("{};{}", parent.path, name),
+        None => name.clone(),
+      };
+      stack.push(Frame {
+        name,
+        path,
+        start: Instant::now(),
+        nested: Duration::default(),
+        counts: Vec::new(),
+      });
+    });
+  }
+
+  pub fn count(name: &'static str) {
+    STACK.with(|stack| {
+      if let Some(frame) = stack.borrow_mut().last_mut() {
+        match frame
+          .counts
+          .iter_mut()
+          .find(|(counter, _)| *counter == name)
+        {
+          Some((_, n)) => *n += 1,
+          None => frame.counts.push((name, 1)),
+        }
+      }
+    });
+  }
+
+  pub fn close() {
+    let event = STACK.with(|stack| {
+      let mut stack = stack.borrow_mut();
+      let frame = stack.pop()?;
+      let duration = frame.start.elapsed();
+      if let Some(parent) = stack.last_mut() {
+        parent.nested += duration;
+      }
+      Some(Event {
+        name: frame.name,
+        path: frame.path,
+        thread: thread(),
+        start: frame.start.saturating_duration_since(*EPOCH),
+        duration,
+        own: duration.saturating_sub(frame.nested),
+        counts: frame.counts,
+      })
+    });
+    if let Some(event) = event {
+      EVENTS.lock().unwrap_or_else(|e| e.into_inner()).push(event);
+    }
+  }
+
+  pub fn take() -> Vec<Event> {
+    mem::take(&mut *EVENTS.lock().unwrap_or_else(|e| e.into_inner()))
+  }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Trace(pub Vec<Event>);
+
+// Every event with the same span name, added up
+#[derive(Debug, PartialEq)]
+pub struct Total {
+  pub name: String,
+  pub calls: u64,
+  // The time spent in the span itself rather than in nested spans
+  pub own: Duration,
+  pub counts: Vec<(&'static str, u64)>,
+}
+
+impl Trace {
+  pub fn len(&self) -> usize {
+    self.0.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  // Chrome's trace event format: one complete ("X") event per span, in microseconds,
+  // with the span's counts as its args
+  pub fn to_chrome(&self) -> String {
+    let events: Vec<String> = self
+      .0
+      .iter()
+      .map(|event| {
+        let args = event
+          .counts
+          .iter()
+          .fold(Value::object(), |args, &(name, n)| args.with(name, n));
+        Value::object()
+          .with("name", event.name.as_str())
+          .with("ph", "X")
+          .with("ts", event.start.as_micros())
+          .with("dur", event.duration.as_micros())
+          .with("pid", 1u32)
+          .with("tid", event.thread)
+          .with("args", args)
+          .to_string()
+      })
+      .collect();
+    format!("[\n{}\n]\n", events.join(",\n"))
+  }
+
+  // One line per distinct stack, with the nanoseconds spent in its innermost span
+  // itself rather than in nested spans
+  pub fn to_folded(&self) -> String {
+    let mut stacks: BTreeMap<&str, u128> = BTreeMap::new();
+    for event in &self.0 {
+      *stacks.entry(&event.path).or_default() += event.own.as_nanos();
+    }
+    stacks
+      .iter()
+      .map(|(path, nanos)| format!("{} {}\n", path, nanos))
+      .collect()
+  }
+
+  // The totals for each span name, in the order the names first closed
+  pub fn summary(&self) -> Vec<Total> {
+    let mut totals: Vec<Total> = Vec::new();
+    for event in &self.0 {
+      let index = match totals.iter().position(|total| total.name == event.name) {
+        Some(index) => index,
+        None => {
+          totals.push(Total {
+            name: event.name.clone(),
+            calls: 0,
+            own: Duration::default(),
+            counts: Vec::new(),
+          });
+          totals.len() - 1
+        }
+      };
+      let total = &mut totals[index];
+      total.calls += 1;
+      total.own += event.own;
+      for &(name, n) in &event.counts {
+        match total
+          .counts
+          .iter_mut()
+          .find(|(counter, _)| *counter == name)
+        {
+          Some((_, sum)) => *sum += n,
+          None => total.counts.push((name, n)),
+        }
+      }
+    }
+    totals
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn event(name: &str, path: &str, start: u64, duration: u64, own: u64) -> Event {
+    Event {
+      name: String::from(name),
+      path: String::from(path),
+      thread: 1,
+      start: Duration::from_micros(start),
+      duration: Duration::from_micros(duration),
+      own: Duration::from_micros(own),
+      counts: Vec::new(),
+    }
+  }
+
+  fn sample() -> Trace {
+    let mut inner = event("day11::mutate", "day11::part1;day11::mutate", 10, 30, 30);
+    inner.counts.push(("seats", 4));
+    Trace(vec![
+      inner.clone(),
+      inner,
+      event("day11::part1", "day11::part1", 5, 100, 40),
+    ])
+  }
+
+  #[test]
+  fn test_chrome() {
+    let chrome = sample().to_chrome();
+    assert!(chrome.starts_with("[\n{\"name\":\"day11::mutate\",\"ph\":\"X\",\"ts\":10,\"dur\":30,\"pid\":1,\"tid\":1,\"args\":{\"seats\":4}},\n"));
+    assert!(chrome.ends_with(",\"args\":{}}\n]\n"));
+    assert_eq!("[\n\n]\n", Trace::default().to_chrome());
+  }
+
+  #[test]
+  fn test_folded() {
+    assert_eq!(
+      "day11::part1 40000\nday11::part1;day11::mutate 60000\n",
+      sample().to_folded()
+    );
+  }
+
+  #[test]
+  fn test_summary() {
+    assert_eq!(
+      vec![
+        Total {
+          name: String::from("day11::mutate"),
+          calls: 2,
+          own: Duration::from_micros(60),
+          counts: vec![("seats", 8)],
+        },
+        Total {
+          name: String::from("day11::part1"),
+          calls: 1,
+          own: Duration::from_micros(40),
+          counts: vec![],
+        },
+      ],
+      sample().summary()
+    );
+  }
+
+  #[cfg(feature = "trace")]
+  #[test]
+  fn test_record() {
+    use std::thread;
+
+    // On a thread of its own, so spans from other tests don't get in the way
+    let thread = thread::spawn(|| {
+      let _outer = span("test::outer");
+      count("loops");
+      for _ in 0..2 {
+        let _inner = span("test::inner");
+        count("loops");
+      }
+      let _ = span("test::unused");
+    });
+    thread.join().unwrap();
+
+    let trace = take();
+    let events: Vec<&Event> = trace
+      .0
+      .iter()
+      .filter(|event| event.name.starts_with("test::"))
+      .collect();
+    let paths: Vec<&str> = events.iter().map(|event| event.path.as_str()).collect();
+    assert_eq!(
+      vec![
+        "test::outer;test::inner",
+        "test::outer;test::inner",
+        "test::outer;test::unused",
+        "test::outer"
+      ],
+      paths
+    );
+    assert_eq!(vec![("loops", 1)], events[0].counts);
+    assert_eq!(vec![("loops", 1)], events[3].counts);
+    assert!(events[3].duration >= events[0].duration + events[1].duration);
+    assert!(events.iter().all(|event| event.thread == events[0].thread));
+  }
+}
@@ -0,0 +1,183 @@
+// `watch <day>`: polls the day's input and source, and when either changes re-runs
+// the day's example tests and both parts through cargo, so edits to the solver are
+// picked up too. Each answer is shown as a diff against the previous run.
+
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+// How often the watched files are checked
+pub const POLL: Duration = Duration::from_millis(500);
+
+// The modification time and length of each watched file, or None for a missing one
+#[derive(Debug, PartialEq)]
+pub struct Stamp(Vec<Option<(SystemTime, u64)>>);
+
+impl Stamp {
+  pub fn new(paths: &[PathBuf]) -> Stamp {
+    Stamp(
+      paths
+        .iter()
+        .map(|path| {
+          let metadata = fs::metadata(path).ok()?;
+          Some((metadata.modified().ok()?, metadata.len()))
+        })
+        .collect(),
+    )
+  }
+
+  // The paths that differ between two stamps of the same list
+  pub fn changed<'a>(&self, other: &Stamp, paths: &'a [PathBuf]) -> Vec<&'a Path> {
+    paths
+      .iter()
+      .zip(self.0.iter().zip(other.0.iter()))
+      .filter(|(_, (a, b))| a != b)
+      .map(|(path, _)| path.as_path())
+      .collect()
+  }
+}
+
+fn cargo(root: &Path) -> Command {
+  let mut command = Command::new(env::var_os("CARGO").unwrap_or_else(|| OsString::from("cargo")));
+  command.current_dir(root).arg("-q");
+  command
+}
+
+// Runs the day's unit tests and the example corpus, returning their output if any
+// failed
+pub fn run_examples(root: &Path, day: u32) -> Result<(), String> {
+  let output = cargo(root)
+    .args(["test", "--lib", "--"])
+    .arg(format!("day{}::", day))
+    .arg("examples::")
+    .output()
+    .map_err(|e| format!("couldn't run cargo: {}", e))?;
+  if output.status.success() {
+    return Ok(());
+  }
+  let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+  text.push_str(&String::from_utf8_lossy(&output.stderr));
+  Err(String::from(text.trim()))
+}
+
+// The answer to one part, or the error it failed with. None if the part isn't
+// implemented (yet).
+pub fn run_part(
+  root: &Path,
+  day: u32,
+  part: u32,
+  input: &Path,
+  timeout: Option<Duration>,
+) -> Option<Result<String, String>> {
+  let mut command = cargo(root);
+  command
+    .args(["run", "--release", "--"])
+    .arg(day.to_string())
+    .arg(part.to_string())
+    .arg("--no-cache")
+    .arg("--input")
+    .arg(input);
+  if let Some(timeout) = timeout {
+    command.arg(format!("--timeout={}ms", timeout.as_millis()));
+  }
+  let output = match command.output() {
+    Ok(output) => output,
+    Err(e) => return Some(Err(format!("couldn't run cargo: {}", e))),
+  };
+  let stdout = String::from_utf8_lossy(&output.stdout);
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  match output.status.code() {
+    Some(0) => Some(Ok(String::from(stdout.trim_end()))),
+    // Only an unimplemented part exits with 2, since the arguments are ours
+    Some(2) => None,
+    _ => Some(Err(String::from(
+      stderr
+        .trim()
+        .strip_prefix("error: ")
+        .unwrap_or(stderr.trim()),
+    ))),
+  }
+}
+
+fn describe(result: &Result<String, String>) -> String {
+  match result {
+    Ok(answer) => answer.clone(),
+    Err(message) => format!("error: {}", message),
+  }
+}
+
+// The lines reporting one part's result, as a diff against its previous result
+pub fn diff(
+  part: u32,
+  previous: Option<&Result<String, String>>,
+  current: &Result<String, String>,
+) -> Vec<String> {
+  let now = describe(current);
+  match previous.map(describe) {
+    None if !now.contains('\n') => vec![format!("part {}: {}", part, now)],
+    Some(before) if before == now && !now.contains('\n') => {
+      vec![format!("part {}: {} (unchanged)", part, now)]
+    }
+    Some(before) if before == now => vec![format!("part {}: unchanged", part)],
+    before => {
+      let mut lines = vec![format!("part {}:", part)];
+      if let Some(before) = before {
+        lines.extend(before.lines().map(|line| format!("  - {}", line)));
+      }
+      lines.extend(now.lines().map(|line| format!("  + {}", line)));
+      lines
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::process;
+
+  #[test]
+  fn test_stamp() {
+    let path = env::temp_dir().join(format!("aoc2020-watch-{}.txt", process::id()));
+    let paths = vec![path.clone()];
+    let missing = Stamp::new(&paths);
+    assert_eq!(Stamp(vec![None]), missing);
+
+    fs::write(&path, "1\n").unwrap();
+    let written = Stamp::new(&paths);
+    assert_eq!(vec![path.as_path()], missing.changed(&written, &paths));
+    assert!(written.changed(&Stamp::new(&paths), &paths).is_empty());
+
+    // Same modification time (on coarse clocks), different length
+    fs::write(&path, "12\n").unwrap();
+    assert_eq!(1, written.changed(&Stamp::new(&paths), &paths).len());
+    fs::remove_file(&path).unwrap();
+  }
+
+  #[test]
+  fn test_diff() {
+    let first: Result<String, String> = Ok(String::from("42"));
+    let second = Ok(String::from("43"));
+    let failed = Err(String::from("day 1 part 1 timed out"));
+
+    assert_eq!(vec!["part 1: 42"], diff(1, None, &first));
+    assert_eq!(
+      vec!["part 1: 42 (unchanged)"],
+      diff(1, Some(&first), &first)
+    );
+    assert_eq!(
+      vec!["part 1:", "  - 42", "  + 43"],
+      diff(1, Some(&first), &second)
+    );
+    assert_eq!(
+      vec!["part 2:", "  - 43", "  + error: day 1 part 1 timed out"],
+      diff(2, Some(&second), &failed)
+    );
+
+    let grid = Ok(String::from("#.\n.#"));
+    assert_eq!(vec!["part 1:", "  + #.", "  + .#"], diff(1, None, &grid));
+    assert_eq!(vec!["part 1: unchanged"], diff(1, Some(&grid), &grid));
+  }
+}